@@ -0,0 +1,71 @@
+//! Uploads a YouTube video to S3 as a multipart upload, without touching the local disk
+//!
+//! Run with `cargo run --example s3_upload --features s3-example -- <bucket> <video id>`, with
+//! the AWS credentials and region taken from the environment.
+
+use aws_sdk_s3::{ primitives::ByteStream, types::{ CompletedMultipartUpload, CompletedPart } };
+use bytes::Bytes;
+use futures_util::{ Stream, StreamExt };
+use rustytdown::YouTubeDownloader;
+
+/// S3 needs every part but the last to be at least 5 MiB
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let (Some(bucket), Some(video_id)) = (args.next(), args.next()) else {
+        eprintln!("Usage: s3_upload <bucket> <video id>");
+        std::process::exit(2);
+    };
+    let key = format!("{video_id}.mp4");
+
+    let downloader = YouTubeDownloader::new()?;
+    let s3 = aws_sdk_s3::Client::new(&aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await);
+    // Resolved first, so a video that can't be downloaded never opens an upload
+    let (parts, size) = downloader.stream_video_parts(&video_id, PART_SIZE).await?;
+
+    let upload = s3.create_multipart_upload().bucket(&bucket).key(&key).send().await?;
+    let upload_id = upload.upload_id().ok_or("S3 returned no upload ID")?;
+
+    // S3 keeps billing for the parts of an upload that is neither completed nor aborted
+    if let Err(e) = upload_parts(&s3, &bucket, &key, upload_id, parts).await {
+        if let Err(abort) = s3.abort_multipart_upload().bucket(&bucket).key(&key).upload_id(upload_id).send().await {
+            eprintln!("Failed to abort upload {upload_id}: {abort}");
+        }
+        return Err(e);
+    }
+    println!("Uploaded {size} bytes to s3://{bucket}/{key}");
+    Ok(())
+}
+
+/// Uploads every part of the stream and completes the multipart upload
+async fn upload_parts(
+    s3: &aws_sdk_s3::Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    mut parts: impl Stream<Item = rustytdown::Result<Bytes>> + Unpin
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut completed = Vec::new();
+    while let Some(part) = parts.next().await {
+        let number = (completed.len() as i32) + 1;
+        let uploaded = s3
+            .upload_part()
+            .bucket(bucket)
+            .key(key)
+            .upload_id(upload_id)
+            .part_number(number)
+            .body(ByteStream::from(part?))
+            .send().await?;
+        completed.push(CompletedPart::builder().part_number(number).set_e_tag(uploaded.e_tag).build());
+    }
+
+    s3.complete_multipart_upload()
+        .bucket(bucket)
+        .key(key)
+        .upload_id(upload_id)
+        .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(completed)).build())
+        .send().await?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::{ collections::HashSet, fmt, path::PathBuf };
+use tokio::{ fs::OpenOptions, io::AsyncWriteExt, sync::Mutex };
+
+use crate::{ Error, Result, YouTubeDownloader };
+
+/// A download archive listing the IDs of videos downloaded in earlier runs
+///
+/// Entries are written as `youtube <id>` lines, the format of youtube-dl's
+/// `--download-archive`, so existing archives can be shared with it. The file is read on
+/// first use; the lock serializes the check-and-append of concurrent batch downloads so
+/// every ID is written once.
+pub(crate) struct Archive {
+    path: PathBuf,
+    ids: Mutex<Option<HashSet<String>>>,
+}
+
+impl Archive {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path, ids: Mutex::new(None) }
+    }
+
+    async fn load(&self) -> Result<HashSet<String>> {
+        match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) =>
+                Ok(
+                    contents
+                        .lines()
+                        .filter_map(|line| line.split_whitespace().last())
+                        .map(String::from)
+                        .collect()
+                ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashSet::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns true if the archive lists `video_id`
+    pub(crate) async fn contains(&self, video_id: &str) -> Result<bool> {
+        let mut ids = self.ids.lock().await;
+        if ids.is_none() {
+            *ids = Some(self.load().await?);
+        }
+        Ok(ids.as_ref().is_some_and(|ids| ids.contains(video_id)))
+    }
+
+    /// Appends `video_id` to the archive unless it is listed already
+    pub(crate) async fn record(&self, video_id: &str) -> Result<()> {
+        let mut ids = self.ids.lock().await;
+        if ids.is_none() {
+            *ids = Some(self.load().await?);
+        }
+        let Some(ids) = ids.as_mut() else {
+            return Ok(());
+        };
+        if ids.contains(video_id) {
+            return Ok(());
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(format!("youtube {video_id}\n").as_bytes()).await?;
+        file.flush().await?;
+        ids.insert(video_id.to_string());
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Archive").field("path", &self.path).finish()
+    }
+}
+
+impl YouTubeDownloader {
+    /// Fails with `Error::Archived` if the archive already lists `video_id`
+    pub(crate) async fn check_archive(&self, video_id: &str) -> Result<()> {
+        match &self.archive {
+            Some(archive) if archive.contains(video_id).await? => {
+                Err(Error::Archived { video_id: video_id.to_string() })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a finished download of `video_id` in the archive
+    pub(crate) async fn record_archive(&self, video_id: &str) -> Result<()> {
+        if let Some(archive) = &self.archive {
+            archive.record(video_id).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use futures_util::{ stream, StreamExt };
+use serde_json::Value;
+use std::{ collections::HashMap, path::{ Path, PathBuf } };
+
+use crate::{
+    captions,
+    fifo,
+    info,
+    CaptionFormat,
+    ConversionOptions,
+    DownloadReport,
+    DownloadRequest,
+    Error,
+    FormatPreference,
+    MediaKind,
+    Result,
+    TransferEvent,
+    VideoInfo,
+    YouTubeDownloader,
+};
+
+/// A kind of file written by `YouTubeDownloader::download_batch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Artifact {
+    /// The downloaded media, `{id}.{ext}` by default
+    Media,
+    /// The thumbnail, `{id}.{ext}` by default
+    Thumbnail,
+    /// A caption track, `{id}.{lang}.{ext}` by default
+    Caption,
+    /// The raw `videoDetails` and the offered captions, `{id}.info.json` by default
+    Info,
+    /// The description text, `{id}.description` by default
+    Description,
+}
+
+impl Artifact {
+    fn default_template(&self) -> &'static str {
+        match self {
+            Self::Media | Self::Thumbnail => "{id}.{ext}",
+            Self::Caption => "{id}.{lang}.{ext}",
+            Self::Info => "{id}.info.json",
+            Self::Description => "{id}.description",
+        }
+    }
+}
+
+/// How `YouTubeDownloader::download_batch` lays out the files of every video
+///
+/// Every `Artifact` is named by its own template, so e.g. media can go to a NAS while
+/// thumbnails go to a local cache. Templates may use `{id}`, `{title}` (sanitized for file
+/// names), `{ext}` and, for captions, `{lang}`. Relative templates are resolved against the
+/// video's directory, absolute ones are used as they are.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ Artifact, BatchOptions, MediaKind };
+///
+/// let options = BatchOptions::new("archive")
+///     .per_video_subdir(true)
+///     .kind(MediaKind::Best)
+///     .thumbnail(true)
+///     .info_json(true)
+///     .captions(["en", "de"])
+///     .template(Artifact::Thumbnail, "/var/cache/thumbnails/{id}.{ext}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    out_dir: PathBuf,
+    per_video_subdir: bool,
+    kind: MediaKind,
+    preference: FormatPreference,
+    conversion: ConversionOptions,
+    concurrency: usize,
+    thumbnail: bool,
+    info_json: bool,
+    description: bool,
+    captions: Vec<String>,
+    caption_format: CaptionFormat,
+    templates: HashMap<Artifact, String>,
+}
+
+impl BatchOptions {
+    /// Creates options writing only the media of every video into `out_dir`
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+            per_video_subdir: false,
+            kind: MediaKind::default(),
+            preference: FormatPreference::default(),
+            conversion: ConversionOptions::default(),
+            concurrency: 1,
+            thumbnail: false,
+            info_json: false,
+            description: false,
+            captions: Vec::new(),
+            caption_format: CaptionFormat::default(),
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Places every video's files in `out_dir/<title>/`, falling back to the video ID for
+    /// titles that sanitize to nothing
+    pub fn per_video_subdir(mut self, enabled: bool) -> Self {
+        self.per_video_subdir = enabled;
+        self
+    }
+
+    /// Sets what the media download of every video should produce, as `DownloadRequest::kind`
+    pub fn kind(mut self, kind: MediaKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the quality constraints for format selection, as `DownloadRequest::preference`
+    pub fn preference(mut self, preference: FormatPreference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    /// Sets the FFmpeg conversion options used for `MediaKind::Audio`
+    pub fn conversion(mut self, conversion: ConversionOptions) -> Self {
+        self.conversion = conversion;
+        self
+    }
+
+    /// Sets how many videos are processed at once, defaulting to one
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Also saves the largest thumbnail as `<video_id>.<extension>`
+    pub fn thumbnail(mut self, enabled: bool) -> Self {
+        self.thumbnail = enabled;
+        self
+    }
+
+    /// Also saves the raw `videoDetails` as `<video_id>.info.json`, with the caption tracks
+    /// offered at download time listed under `captions`
+    pub fn info_json(mut self, enabled: bool) -> Self {
+        self.info_json = enabled;
+        self
+    }
+
+    /// Also saves the description text as `<video_id>.description`
+    pub fn description(mut self, enabled: bool) -> Self {
+        self.description = enabled;
+        self
+    }
+
+    /// Also saves the caption tracks of these languages, skipping languages that aren't offered
+    pub fn captions<I, S>(mut self, languages: I) -> Self where I: IntoIterator<Item = S>, S: Into<String> {
+        self.captions = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the format captions are saved in
+    pub fn caption_format(mut self, format: CaptionFormat) -> Self {
+        self.caption_format = format;
+        self
+    }
+
+    /// Sets the output path template of an artifact type
+    pub fn template(mut self, artifact: Artifact, template: impl Into<String>) -> Self {
+        self.templates.insert(artifact, template.into());
+        self
+    }
+
+    async fn artifact_path(&self, artifact: Artifact, dir: &Path, vars: &[(&str, &str)]) -> Result<PathBuf> {
+        artifact_path(&self.templates, artifact, dir, vars).await
+    }
+}
+
+impl YouTubeDownloader {
+    /// Downloads many videos together with their side files according to `options`
+    ///
+    /// The results are returned in the same order as `video_ids`, one per ID, so a failing ID
+    /// doesn't fail the batch.
+    ///
+    /// # Arguments
+    /// * `video_ids` - The YouTube video IDs
+    /// * `options` - The artifacts to save and where to put them
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ BatchOptions, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let options = BatchOptions::new("archive").per_video_subdir(true).thumbnail(true);
+    /// for report in downloader.download_batch(&["dQw4w9WgXcQ", "9bZkp7q19f0"], &options).await {
+    ///     match report {
+    ///         Ok(report) => println!("{}", report.path),
+    ///         Err(e) => eprintln!("{e}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_batch(&self, video_ids: &[&str], options: &BatchOptions) -> Vec<Result<DownloadReport>> {
+        stream::iter(video_ids)
+            .map(|video_id| self.download_batch_item(video_id, options))
+            .buffered(options.concurrency)
+            .collect().await
+    }
+
+    async fn download_batch_item(&self, video_id: &str, options: &BatchOptions) -> Result<DownloadReport> {
+        // Keeps a shutdown waiting until the item's files are moved into place
+        let _in_flight = self.shutdown_state.enter()?;
+        // Checked before any side artifact is written, `download` records the ID once done
+        self.check_archive(video_id).await?;
+        self.jitter().await;
+        let json = self.get_player_response(video_id).await?;
+        let info = VideoInfo::from_player_response(&json)?;
+
+        let title = sanitize_file_name(&info.title).unwrap_or_else(|| video_id.to_string());
+        let dir = match options.per_video_subdir {
+            true => options.out_dir.join(&title),
+            false => options.out_dir.clone(),
+        };
+        tokio::fs::create_dir_all(&dir).await?;
+        let vars = [("id", video_id), ("title", title.as_str())];
+
+        if options.info_json {
+            let path = options.artifact_path(Artifact::Info, &dir, &vars).await?;
+            tokio::fs::write(path, info_sidecar(&json)?).await?;
+        }
+
+        if options.description {
+            let path = options.artifact_path(Artifact::Description, &dir, &vars).await?;
+            tokio::fs::write(path, info::description(&json)).await?;
+        }
+
+        if options.thumbnail {
+            if let Some(url) = &info.thumbnail_url {
+                let extension = match url.split('?').next().unwrap_or_default().rsplit('.').next() {
+                    Some("webp") => "webp",
+                    Some("png") => "png",
+                    _ => "jpg",
+                };
+                let thumbnail_vars = [vars[0], vars[1], ("ext", extension)];
+                let path = options.artifact_path(Artifact::Thumbnail, &dir, &thumbnail_vars).await?;
+                self.download_thumbnail(url, &path).await?;
+            }
+        }
+
+        let tracks = captions::parse_caption_tracks(&json);
+        for language in &options.captions {
+            if let Some(track) = tracks.iter().find(|track| &track.language_code == language) {
+                let caption_vars = [
+                    vars[0],
+                    vars[1],
+                    ("lang", language.as_str()),
+                    ("ext", options.caption_format.extension()),
+                ];
+                let path = options.artifact_path(Artifact::Caption, &dir, &caption_vars).await?;
+                let path = path.to_string_lossy();
+                self.download_caption_url(&track.base_url, options.caption_format, None, &path).await?;
+            }
+        }
+
+        let request = DownloadRequest::new(video_id)
+            .kind(options.kind)
+            .preference(options.preference.clone())
+            .conversion(options.conversion.clone())
+            .output(&dir);
+        let mut report = self.download(request).await?;
+
+        // The extension is only known once the format was selected, so the media is moved to
+        // its templated path afterwards
+        if options.templates.contains_key(&Artifact::Media) {
+            let downloaded = PathBuf::from(&report.path);
+            let extension = downloaded.extension().and_then(|e| e.to_str()).unwrap_or_default().to_string();
+            let media_vars = [vars[0], vars[1], ("ext", extension.as_str())];
+            let path = options.artifact_path(Artifact::Media, &dir, &media_vars).await?;
+            self.move_file(&downloaded, &path).await?;
+            report.path = path.to_string_lossy().into_owned();
+        }
+        Ok(report)
+    }
+
+    async fn download_thumbnail(&self, url: &str, path: &Path) -> Result<()> {
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(Error::Api(format!("Thumbnail request failed with status: {}", response.status())));
+        }
+
+        tokio::fs::write(path, response.bytes().await?).await?;
+        Ok(())
+    }
+}
+
+/// Renders the path of an artifact from its template, creating its parent directory
+pub(crate) async fn artifact_path(
+    templates: &HashMap<Artifact, String>,
+    artifact: Artifact,
+    dir: &Path,
+    vars: &[(&str, &str)]
+) -> Result<PathBuf> {
+    let template = templates
+        .get(&artifact)
+        .map(String::as_str)
+        .unwrap_or(artifact.default_template());
+    let rendered = vars
+        .iter()
+        .fold(template.to_string(), |path, (key, value)| path.replace(&format!("{{{key}}}"), value));
+
+    let path = dir.join(rendered);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    Ok(path)
+}
+
+impl YouTubeDownloader {
+    /// Moves a finished file into place, copying it when the destination is on another
+    /// filesystem
+    ///
+    /// A rename across filesystems fails with `EXDEV`, in which case the file is copied to a
+    /// `.part` file next to the destination and renamed over it, so the destination never
+    /// holds a partial file. The copy streams through the kernel rather than memory. Whether
+    /// the fallback was taken is reported as `TransferEvent::Moved`.
+    pub(crate) async fn move_file(&self, from: &Path, to: &Path) -> Result<()> {
+        // Renaming over a FIFO would replace it, so its reader gets the bytes streamed instead
+        if fifo::is_fifo(to).await {
+            let mut source = tokio::fs::File::open(from).await?;
+            let mut pipe = tokio::fs::OpenOptions::new().write(true).open(to).await?;
+            tokio::io::copy(&mut source, &mut pipe).await?;
+            tokio::fs::remove_file(from).await?;
+            self.emit(TransferEvent::Moved {
+                from: from.to_string_lossy().into_owned(),
+                to: to.to_string_lossy().into_owned(),
+                copied: true,
+            });
+            return Ok(());
+        }
+
+        let copied = match tokio::fs::rename(from, to).await {
+            Ok(()) => false,
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                let mut part = to.as_os_str().to_owned();
+                part.push(".part");
+                let part = PathBuf::from(part);
+                if let Err(e) = tokio::fs::copy(from, &part).await {
+                    let _ = tokio::fs::remove_file(&part).await;
+                    return Err(e.into());
+                }
+                tokio::fs::rename(&part, to).await?;
+                tokio::fs::remove_file(from).await?;
+                true
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        };
+
+        self.emit(TransferEvent::Moved {
+            from: from.to_string_lossy().into_owned(),
+            to: to.to_string_lossy().into_owned(),
+            copied,
+        });
+        Ok(())
+    }
+}
+
+/// Builds the `info.json` sidecar: the `videoDetails` plus the caption tracks on offer
+///
+/// The tracks are listed even if none were downloaded, so the record shows which subtitles
+/// existed at download time.
+pub(crate) fn info_sidecar(json: &Value) -> Result<Vec<u8>> {
+    let mut details = json["videoDetails"].clone();
+    let captions: Vec<Value> = captions::parse_caption_tracks(json)
+        .into_iter()
+        .map(|track| {
+            serde_json::json!({
+                "languageCode": track.language_code,
+                "name": track.name,
+                "url": track.base_url,
+                "autoGenerated": track.auto_generated,
+                "translatable": track.translatable,
+            })
+        })
+        .collect();
+    if let Some(details) = details.as_object_mut() {
+        details.insert("captions".to_string(), Value::Array(captions));
+    }
+    serde_json::to_vec_pretty(&details).map_err(|e| Error::Api(e.to_string()))
+}
+
+/// Turns a video title into a portable directory name, or `None` if nothing usable is left
+pub(crate) fn sanitize_file_name(title: &str) -> Option<String> {
+    let sanitized: String = title
+        .chars()
+        .map(|c| {
+            match c {
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            }
+        })
+        .take(200)
+        .collect();
+    let sanitized = sanitized.trim().trim_matches('.').trim();
+    (!sanitized.is_empty()).then(|| sanitized.to_string())
+}
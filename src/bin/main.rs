@@ -1,10 +1,79 @@
-use rustytdown::YouTubeDownloader;
+use rustytdown::{ BatchOptions, MediaKind, VideoId, YouTubeDownloader };
+
+const USAGE: &str = "Usage: rustytdown [--batch-file <path>] [--concurrency <n>] [<video id or URL>...]";
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    
+
     let downloader = YouTubeDownloader::new()?;
-    downloader.download_and_convert("dQw4w9WgXcQ").await?;
-    
+
+    let mut video_ids = Vec::new();
+    let mut invalid = 0;
+    let mut concurrency = 1;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch-file" => {
+                let path = args.next().ok_or(USAGE)?;
+                let (ids, skipped) = read_batch_file(&path)?;
+                video_ids.extend(ids);
+                invalid += skipped;
+            }
+            "--concurrency" => {
+                concurrency = args.next().ok_or(USAGE)?.parse().map_err(|_| USAGE)?;
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            input => video_ids.push(VideoId::from_url(input)?),
+        }
+    }
+
+    if video_ids.is_empty() && invalid == 0 {
+        downloader.download_and_convert("dQw4w9WgXcQ").await?;
+        return Ok(());
+    }
+
+    let ids: Vec<&str> = video_ids.iter().map(VideoId::as_str).collect();
+    let options = BatchOptions::new(".").kind(MediaKind::Audio).concurrency(concurrency);
+    let results = downloader.download_batch(&ids, &options).await;
+
+    let mut failed = invalid;
+    for (video_id, result) in ids.iter().zip(&results) {
+        if let Err(e) = result {
+            eprintln!("{video_id}: {e}");
+            failed += 1;
+        }
+    }
+    println!("{} succeeded, {} failed", results.len() + invalid - failed, failed);
+
+    if failed > 0 {
+        std::process::exit(1);
+    }
     Ok(())
 }
+
+/// Reads one video ID or URL per line, skipping blank lines and `#` comments
+///
+/// Lines that are no valid ID or URL are reported and counted rather than failing the run,
+/// returning the valid IDs and the number of invalid lines.
+fn read_batch_file(path: &str) -> Result<(Vec<VideoId>, usize), Box<dyn std::error::Error>> {
+    let mut video_ids = Vec::new();
+    let mut invalid = 0;
+    let lines = std::fs::read_to_string(path)?;
+    for (number, line) in lines.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match VideoId::from_url(line) {
+            Ok(video_id) => video_ids.push(video_id),
+            Err(e) => {
+                eprintln!("{path}:{}: {e}", number + 1);
+                invalid += 1;
+            }
+        }
+    }
+    Ok((video_ids, invalid))
+}
@@ -0,0 +1,73 @@
+use std::{ path::Path, sync::atomic::Ordering };
+
+use crate::{ Error, FormatInfo, Result, YouTubeDownloader };
+
+impl YouTubeDownloader {
+    /// Downloads every format of a video with a direct URL, for archiving all renditions
+    ///
+    /// Each format is written to `<out_dir>/<video_id>.<itag>.<extension>`, one after the
+    /// other. The results are returned in format list order next to their format, so a
+    /// failing format doesn't stop the others. Set `max_file_size` and `disk_budget` on the
+    /// builder to keep this from filling the disk.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `out_dir` - The directory to write into, created if missing
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::path::Path;
+    ///
+    /// let downloader = YouTubeDownloader::builder().disk_budget(2 * 1024 * 1024 * 1024).build()?;
+    /// for (format, result) in downloader.download_all_formats("dQw4w9WgXcQ", Path::new("renditions")).await? {
+    ///     match result {
+    ///         Ok(path) => println!("itag {} saved to {path}", format.itag),
+    ///         Err(e) => eprintln!("itag {} failed: {e}", format.itag),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_all_formats(
+        &self,
+        video_id: &str,
+        out_dir: &Path
+    ) -> Result<Vec<(FormatInfo, Result<String>)>> {
+        let formats = self.list_formats(video_id).await?;
+        tokio::fs::create_dir_all(out_dir).await?;
+
+        let mut results = Vec::new();
+        for format in formats.into_iter().filter(|format| format.url.is_some()) {
+            let output = out_dir.join(format!("{video_id}.{}.{}", format.itag, format.extension()));
+            let result = self.download_format_to(&format, &output).await;
+            results.push((format, result));
+        }
+        Ok(results)
+    }
+
+    /// Enforces `max_file_size` and reserves the size of `format` from the `disk_budget`,
+    /// returning the reserved bytes
+    pub(crate) fn reserve_disk(&self, format: &FormatInfo) -> Result<u64> {
+        let size = format.estimated_size().unwrap_or(0);
+        if let Some(limit) = self.max_file_size.filter(|limit| size > *limit) {
+            return Err(Error::FileTooLarge { size, limit });
+        }
+
+        let Some(budget) = self.disk_budget else {
+            return Ok(0);
+        };
+        let used = self.disk_used.fetch_add(size, Ordering::SeqCst);
+        if used + size > budget {
+            self.disk_used.fetch_sub(size, Ordering::SeqCst);
+            return Err(Error::DiskBudgetExceeded { size, remaining: budget.saturating_sub(used) });
+        }
+        Ok(size)
+    }
+
+    /// Gives bytes reserved for a failed download back to the `disk_budget`
+    pub(crate) fn release_disk(&self, reserved: u64) {
+        self.disk_used.fetch_sub(reserved, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,933 @@
+use reqwest::{ Certificate, Client, ClientBuilder, redirect::Policy };
+use std::{ net::SocketAddr, path::{ Path, PathBuf }, sync::Arc, time::Duration };
+
+use crate::{
+    archive::Archive,
+    cipher::SharedSolver,
+    dedupe::DedupIndex,
+    metrics::MetricsHook,
+    progress::ProgressCallback,
+    retry::RetryCallback,
+    DownloadStrategy,
+    Error,
+    PlayerEndpoint,
+    ProgressEvent,
+    RateGovernor,
+    Result,
+    RetryInfo,
+    RetryPolicy,
+    SignatureSolver,
+    TransferEvent,
+    YouTubeDownloader,
+};
+
+/// How long connecting to a server may take by default
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+/// How long a response may go without receiving data by default before it counts as stalled
+const DEFAULT_READ_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default size of the chunks of a ranged download, 10 MiB
+const DEFAULT_RANGE_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+pub(crate) type UrlRewriter = Arc<dyn Fn(String) -> String + Send + Sync>;
+
+/// Builder for a `YouTubeDownloader` with custom configuration
+///
+/// # Example
+/// ```
+/// use rustytdown::YouTubeDownloader;
+///
+/// let downloader = YouTubeDownloader::builder().build().unwrap();
+/// ```
+pub struct YouTubeDownloaderBuilder {
+    client: ClientBuilder,
+    dedupe_store: Option<PathBuf>,
+    metrics_hook: Option<MetricsHook>,
+    on_progress: Option<ProgressCallback>,
+    resume: bool,
+    url_rewriter: Option<UrlRewriter>,
+    retry_policy: RetryPolicy,
+    prefetch_bytes: Option<usize>,
+    parallel_chunks: u8,
+    max_connections_per_download: Option<u8>,
+    signature_solver: Option<SharedSolver>,
+    progress_interval: Option<Duration>,
+    archive: Option<PathBuf>,
+    range_chunk_size: u64,
+    referer: Option<String>,
+    origin: Option<String>,
+    max_file_size: Option<u64>,
+    disk_budget: Option<u64>,
+    slow_start: Option<Duration>,
+    on_retry: Option<RetryCallback>,
+    rate_governor: Option<RateGovernor>,
+    root_certificate_files: Vec<PathBuf>,
+    download_strategy: DownloadStrategy,
+    auto_update_client_version: bool,
+    dedup_index: Option<PathBuf>,
+    compress_api_responses: bool,
+    compress_media: bool,
+    player_endpoints: Vec<PlayerEndpoint>,
+    request_jitter: Option<Duration>,
+    read_stall_timeout: Duration,
+    #[cfg(feature = "replay")]
+    replay: crate::replay::ReplayConfig,
+}
+
+impl std::fmt::Debug for YouTubeDownloaderBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("YouTubeDownloaderBuilder");
+        debug
+            .field("client", &self.client)
+            .field("dedupe_store", &self.dedupe_store)
+            .field("metrics_hook", &self.metrics_hook.is_some())
+            .field("on_progress", &self.on_progress.is_some())
+            .field("resume", &self.resume)
+            .field("url_rewriter", &self.url_rewriter.is_some())
+            .field("retry_policy", &self.retry_policy)
+            .field("prefetch_bytes", &self.prefetch_bytes)
+            .field("parallel_chunks", &self.parallel_chunks)
+            .field("max_connections_per_download", &self.max_connections_per_download)
+            .field("signature_solver", &self.signature_solver.is_some())
+            .field("progress_interval", &self.progress_interval)
+            .field("archive", &self.archive)
+            .field("range_chunk_size", &self.range_chunk_size)
+            .field("referer", &self.referer)
+            .field("origin", &self.origin)
+            .field("max_file_size", &self.max_file_size)
+            .field("disk_budget", &self.disk_budget)
+            .field("slow_start", &self.slow_start)
+            .field("on_retry", &self.on_retry.is_some())
+            .field("rate_governor", &self.rate_governor)
+            .field("root_certificate_files", &self.root_certificate_files)
+            .field("download_strategy", &self.download_strategy)
+            .field("auto_update_client_version", &self.auto_update_client_version)
+            .field("dedup_index", &self.dedup_index)
+            .field("compress_api_responses", &self.compress_api_responses)
+            .field("compress_media", &self.compress_media)
+            .field("player_endpoints", &self.player_endpoints)
+            .field("request_jitter", &self.request_jitter)
+            .field("read_stall_timeout", &self.read_stall_timeout);
+        #[cfg(feature = "replay")]
+        debug.field("replay", &self.replay);
+        debug.finish()
+    }
+}
+
+impl YouTubeDownloaderBuilder {
+    pub(crate) fn new() -> Self {
+        Self {
+            client: Client::builder()
+                .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+                .read_timeout(DEFAULT_READ_STALL_TIMEOUT),
+            dedupe_store: None,
+            metrics_hook: None,
+            on_progress: None,
+            resume: false,
+            url_rewriter: None,
+            retry_policy: RetryPolicy::default(),
+            prefetch_bytes: None,
+            parallel_chunks: 1,
+            max_connections_per_download: None,
+            signature_solver: None,
+            progress_interval: None,
+            archive: None,
+            range_chunk_size: DEFAULT_RANGE_CHUNK_SIZE,
+            referer: None,
+            origin: None,
+            max_file_size: None,
+            disk_budget: None,
+            slow_start: None,
+            on_retry: None,
+            rate_governor: None,
+            root_certificate_files: Vec::new(),
+            download_strategy: DownloadStrategy::default(),
+            auto_update_client_version: false,
+            dedup_index: None,
+            compress_api_responses: true,
+            compress_media: false,
+            player_endpoints: vec![PlayerEndpoint::default()],
+            request_jitter: None,
+            read_stall_timeout: DEFAULT_READ_STALL_TIMEOUT,
+            #[cfg(feature = "replay")]
+            replay: Default::default(),
+        }
+    }
+
+    /// Overrides DNS resolution for a hostname, pinning it to a fixed address
+    ///
+    /// Useful to target a specific googlevideo edge node. The port of `addr` is ignored in
+    /// favour of the port of the requested URL.
+    ///
+    /// # Arguments
+    /// * `host` - The hostname to override (e.g. "rr1---sn-abc.googlevideo.com")
+    /// * `addr` - The address requests to `host` should connect to
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .resolve_host("www.youtube.com", "142.250.185.78:443".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn resolve_host(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.client = self.client.resolve(host, addr);
+        self
+    }
+
+    /// Adds a trusted root certificate, e.g. the CA of a TLS-intercepting corporate proxy
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let pem = std::fs::read("corporate-ca.pem").unwrap();
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .add_root_certificate(reqwest::Certificate::from_pem(&pem).unwrap())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_root_certificate(mut self, certificate: Certificate) -> Self {
+        self.client = self.client.add_root_certificate(certificate);
+        self
+    }
+
+    /// Trusts the root certificates in a PEM bundle or DER file, loaded when building
+    ///
+    /// The file-based counterpart to `add_root_certificate` for CA bundles exported by IT
+    /// departments. A PEM file may hold several certificates, all of them are added. A file
+    /// that can't be read or parsed makes `build` fail.
+    ///
+    /// # Arguments
+    /// * `path` - The PEM or DER encoded certificate file
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .add_root_certificate_file("/etc/ssl/corporate-ca.pem")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn add_root_certificate_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.root_certificate_files.push(path.into());
+        self
+    }
+
+    /// Disables TLS certificate validation entirely
+    ///
+    /// # Warning
+    /// Any certificate is trusted, including expired ones and ones for other hosts, which makes
+    /// every request open to man-in-the-middle attacks. Only use this inside trusted networks
+    /// where `add_root_certificate` isn't an option.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.client = self.client.danger_accept_invalid_certs(accept);
+        self
+    }
+
+    /// Sets how long connecting to a server may take, 20 seconds by default
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.connect_timeout(timeout);
+        self
+    }
+
+    /// Sets how long a response may go without receiving any data, 30 seconds by default
+    ///
+    /// The timer restarts with every chunk received, so a slow but steady download of any
+    /// length completes, while a download whose connection stops delivering fails with
+    /// `Error::Stalled`. API requests are held to the same limit.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .connect_timeout(Duration::from_secs(5))
+    ///     .read_stall_timeout(Duration::from_secs(60))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn read_stall_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.read_timeout(timeout);
+        self.read_stall_timeout = timeout;
+        self
+    }
+
+    /// Closes pooled connections that have been idle for longer than `timeout`
+    ///
+    /// CDN edges silently drop idle connections, and reusing such a connection fails with a
+    /// "connection reset". A timeout below the CDN's keeps long-lived downloaders from
+    /// picking up stale connections.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .pool_idle_timeout(Duration::from_secs(30))
+    ///     .tcp_keepalive(Duration::from_secs(15))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.pool_idle_timeout(timeout);
+        self
+    }
+
+    /// Sends TCP keepalive probes on idle connections every `interval`
+    ///
+    /// Keeps NAT and firewall state alive between requests of a long-running service, so
+    /// pooled connections stay usable.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.client = self.client.tcp_keepalive(interval);
+        self
+    }
+
+    /// Limits how many redirects a request follows, 10 by default
+    ///
+    /// A low limit cuts redirect loops short. With 0 no redirect is followed and the 3xx
+    /// response is returned as is, so its `Location` can be inspected.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder().max_redirects(3).build().unwrap();
+    /// ```
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        let policy = if max == 0 { Policy::none() } else { Policy::limited(max) };
+        self.client = self.client.redirect(policy);
+        self
+    }
+
+    /// Deduplicates finished downloads through a content-addressed store directory
+    ///
+    /// Every downloaded file is hashed with SHA-256. If a file with the same hash is already
+    /// in the store the new copy is discarded and replaced by a link to the stored one, so
+    /// re-uploads of identical content only take up disk space once.
+    ///
+    /// # Arguments
+    /// * `store` - The directory holding one file per content hash
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .dedupe_store("archive/.store".into())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn dedupe_store(mut self, store: PathBuf) -> Self {
+        self.dedupe_store = Some(store);
+        self
+    }
+
+    /// Keeps a JSON index of content hashes to detect the same media downloaded under other IDs
+    ///
+    /// Every finished download is hashed with SHA-256 and looked up in the index, a JSON object
+    /// mapping hashes to the absolute path of the first file with that content. A repeated
+    /// download is replaced by a hard link to the indexed file, or a symlink across
+    /// filesystems; new content is added to the index. Unlike `dedupe_store` the files stay
+    /// where they were downloaded. Indexed files that were deleted since are replaced by the
+    /// new download.
+    ///
+    /// # Arguments
+    /// * `path` - The JSON index file, created on the first new download
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .dedup_index("archive/hashes.json")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn dedup_index(mut self, path: impl Into<PathBuf>) -> Self {
+        self.dedup_index = Some(path.into());
+        self
+    }
+
+    /// Sends a `Referer` with the player request, naming the page the video is embedded on
+    ///
+    /// Embed-restricted videos only return usable formats to requests coming from a site
+    /// allowed to embed them. The referer is also passed to the player as the embedding URL.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .referer("https://example.com/watch-page")
+    ///     .origin("https://example.com")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn referer(mut self, referer: impl Into<String>) -> Self {
+        self.referer = Some(referer.into());
+        self
+    }
+
+    /// Sends an `Origin` with the player request, the site allowed to embed the video
+    pub fn origin(mut self, origin: impl Into<String>) -> Self {
+        self.origin = Some(origin.into());
+        self
+    }
+
+    /// Refuses to download formats larger than `bytes`
+    ///
+    /// Checked against the format's reported or estimated size before anything is fetched,
+    /// failing with `Error::FileTooLarge`. Applies to the methods downloading a selected
+    /// format, such as `download_format` and `download_all_formats`.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .max_file_size(500 * 1024 * 1024)
+    ///     .disk_budget(4 * 1024 * 1024 * 1024)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Caps the total bytes the downloader and its clones write across format downloads
+    ///
+    /// Each format's size is reserved before it is fetched, and released again if the
+    /// download fails. A format that would overrun the budget fails with
+    /// `Error::DiskBudgetExceeded`. Formats of unknown size aren't counted.
+    pub fn disk_budget(mut self, bytes: u64) -> Self {
+        self.disk_budget = Some(bytes);
+        self
+    }
+
+    /// Ramps single-connection downloads up over `ramp` instead of starting at full speed
+    ///
+    /// Downloads begin capped at 256 KiB/s, the cap doubles every second and is lifted once
+    /// `ramp` has passed. Some edges throttle clients that request at full speed from the
+    /// first byte sooner than ones ramping up gradually. Parallel chunked downloads aren't
+    /// paced.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .slow_start(Duration::from_secs(5))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn slow_start(mut self, ramp: Duration) -> Self {
+        self.slow_start = Some(ramp);
+        self
+    }
+
+    /// Waits a random delay of up to `max` before the player request of every batch item
+    ///
+    /// A batch or playlist download starting many items at once sends a burst of player
+    /// requests, which trips rate limiting sooner than the same requests spread out. Each
+    /// item waits between zero and `max` first, so the requests are staggered.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .request_jitter(Duration::from_secs(3))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn request_jitter(mut self, max: Duration) -> Self {
+        self.request_jitter = Some(max);
+        self
+    }
+
+    /// Caps the download rate with a governor that can be shared with other downloaders
+    ///
+    /// Every chunk received by any download of this downloader counts against the governor,
+    /// whichever strategy fetches it. See `RateGovernor` for sharing one ceiling between
+    /// several downloaders.
+    ///
+    /// # Arguments
+    /// * `governor` - The shared rate ceiling
+    pub fn rate_governor(mut self, governor: RateGovernor) -> Self {
+        self.rate_governor = Some(governor);
+        self
+    }
+
+    /// Sets the size of the chunks requested from URLs that only serve explicit ranges
+    ///
+    /// Googlevideo URLs without `ratebypass=yes`, typically adaptive streams, throttle or
+    /// cut off a single long GET. They are downloaded as consecutive `&range=start-end`
+    /// requests of this many bytes instead. Defaults to 10 MiB, 0 disables the ranged
+    /// strategy.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .range_chunk_size(4 * 1024 * 1024)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn range_chunk_size(mut self, bytes: u64) -> Self {
+        self.range_chunk_size = bytes;
+        self
+    }
+
+    /// Sets how downloads request their bytes, see `DownloadStrategy`
+    ///
+    /// `DownloadStrategy::Ranged` replaces both the parallel chunking and the automatic
+    /// googlevideo ranges with sequential ranges of a fixed size.
+    pub fn download_strategy(mut self, strategy: DownloadStrategy) -> Self {
+        self.download_strategy = strategy;
+        self
+    }
+
+    /// Sets the endpoints `get_player_response` tries in order, see `PlayerEndpoint`
+    ///
+    /// Defaults to `PlayerEndpoint::AndroidPlayer` alone. An empty list keeps the default.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::{ PlayerEndpoint, YouTubeDownloader };
+    ///
+    /// // Force the web player to debug a failing Android client
+    /// let downloader = YouTubeDownloader::builder().player_endpoints([PlayerEndpoint::WebPlayer]).build().unwrap();
+    /// ```
+    pub fn player_endpoints(mut self, endpoints: impl IntoIterator<Item = PlayerEndpoint>) -> Self {
+        let endpoints: Vec<PlayerEndpoint> = endpoints.into_iter().collect();
+        if !endpoints.is_empty() {
+            self.player_endpoints = endpoints;
+        }
+        self
+    }
+
+    /// Fetches the current web client version from YouTube instead of the pinned one
+    ///
+    /// The version is read from the `ytcfg` of the YouTube home page on the first `Web`
+    /// innertube request and cached for the downloader and its clones. A request rejected
+    /// with `400 Bad Request` refreshes the cached version and is retried once. If the page
+    /// can't be fetched the pinned `InnertubeClient::version` is used.
+    ///
+    /// YouTube publishes no Android version, so the default `PlayerEndpoint::AndroidPlayer`
+    /// keeps its pinned version; when that is rejected with `400 Bad Request` the player
+    /// request is sent once more as the web client with the fetched version. Its formats may
+    /// then need a `signature_solver`, like those of `PlayerEndpoint::WebPlayer`.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder().auto_update_client_version(true).build().unwrap();
+    /// ```
+    pub fn auto_update_client_version(mut self, enabled: bool) -> Self {
+        self.auto_update_client_version = enabled;
+        self
+    }
+
+    /// Asks for gzip or brotli compressed innertube responses, enabled by default
+    ///
+    /// The player and browse JSON compresses well, so this only needs disabling to inspect
+    /// raw traffic or for proxies that mangle compressed bodies.
+    pub fn compress_api_responses(mut self, enabled: bool) -> Self {
+        self.compress_api_responses = enabled;
+        self
+    }
+
+    /// Asks for compressed media downloads, disabled by default
+    ///
+    /// Media is already compressed, so gzip only costs CPU on both ends, and a compressed
+    /// response to a range request doesn't line up with the file's byte offsets. Media
+    /// requests therefore send `Accept-Encoding: identity` unless this is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .compress_api_responses(true)
+    ///     .compress_media(false)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn compress_media(mut self, enabled: bool) -> Self {
+        self.compress_media = enabled;
+        self
+    }
+
+    /// Records downloaded video IDs in an archive file and skips IDs already listed there
+    ///
+    /// Like youtube-dl's `--download-archive`, this avoids re-downloading across separate
+    /// runs. The methods downloading by video ID fail with `Error::Archived` for a listed ID,
+    /// which batch callers can treat as a skip, and append the ID once the download finished.
+    /// Concurrent downloads of one downloader append safely; the file uses youtube-dl's
+    /// `youtube <id>` line format.
+    ///
+    /// # Arguments
+    /// * `path` - The archive file, created on the first recorded download
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .archive_file("archive.txt".into())
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn archive_file(mut self, path: PathBuf) -> Self {
+        self.archive = Some(path);
+        self
+    }
+
+    /// Registers a hook invoked with a `TransferEvent` at key points of every transfer
+    ///
+    /// The hook runs inline on the download task, so it should hand data off quickly
+    /// (e.g. increment a counter) rather than block.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::{ TransferEvent, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .metrics_hook(|event| {
+    ///         if let TransferEvent::FirstByte { ttfb, .. } = event {
+    ///             println!("ttfb_seconds {}", ttfb.as_secs_f64());
+    ///         }
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn metrics_hook(mut self, hook: impl Fn(&TransferEvent) + Send + Sync + 'static) -> Self {
+        self.metrics_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Registers a callback receiving progress and lifecycle events
+    ///
+    /// Besides byte progress, the callback sees discrete `ProgressEvent`s such as
+    /// `ConvertingStarted` and `Completed`, so a UI can switch states without parsing the
+    /// terminal progress bar.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::{ ProgressEvent, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .on_progress(|event| {
+    ///         match event {
+    ///             ProgressEvent::Downloading(progress) => println!("{} bytes", progress.downloaded),
+    ///             ProgressEvent::ConvertingStarted => println!("Converting..."),
+    ///             _ => {}
+    ///         }
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn on_progress(mut self, callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Registers a callback invoked before every retry
+    ///
+    /// The callback learns the phase, the attempt out of how many, the delay and the error
+    /// behind the retry, so a UI can show "retrying (2/5)" instead of looking hung. It runs
+    /// inline before the delay starts.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .on_retry(|info| {
+    ///         eprintln!("Retrying ({}/{}) in {:?}: {}", info.attempt, info.max_retries, info.delay, info.error);
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn on_retry(mut self, callback: impl Fn(RetryInfo) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(callback));
+        self
+    }
+
+    /// Resumes downloads from a partial file left over by an earlier attempt
+    ///
+    /// The remaining bytes are requested with a `Range` header. Servers answering `206` are
+    /// appended to, while servers ignoring the range and answering `200` cause the file to be
+    /// truncated and downloaded from the start. The path taken is reported as a
+    /// `ResumeOutcome` in `TransferEvent::Completed`.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder().resume(true).build().unwrap();
+    /// ```
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    /// Registers a function applied to every direct media URL before it is downloaded
+    ///
+    /// This can redirect googlevideo URLs to an internal caching proxy, or to a local mock
+    /// server in tests.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .url_rewriter(|url| url.replace("https://", "http://cache.internal/"))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn url_rewriter(mut self, rewriter: impl Fn(String) -> String + Send + Sync + 'static) -> Self {
+        self.url_rewriter = Some(Arc::new(rewriter));
+        self
+    }
+
+    /// Sets how failed operations are retried
+    ///
+    /// When FFmpeg rejects a download as truncated or corrupt, the source is downloaded again
+    /// and the conversion retried up to `max_retries` times. Failures caused by bad arguments
+    /// are never retried. Defaults to a single retry after one second. Replaces any per-phase
+    /// counts set before, so call `resolve_retries` and friends afterwards.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::{ RetryPolicy, YouTubeDownloader };
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .retry_policy(RetryPolicy { max_retries: 3, base_delay: Duration::from_secs(2), ..Default::default() })
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Sets how often a player request failing in transit is retried, overriding
+    /// `RetryPolicy::max_retries`
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .resolve_retries(5)
+    ///     .download_retries(1)
+    ///     .conversion_retries(0)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn resolve_retries(mut self, retries: u32) -> Self {
+        self.retry_policy.resolve_retries = Some(retries);
+        self
+    }
+
+    /// Sets how often a failed or truncated media download is retried, overriding
+    /// `RetryPolicy::max_retries`
+    pub fn download_retries(mut self, retries: u32) -> Self {
+        self.retry_policy.download_retries = Some(retries);
+        self
+    }
+
+    /// Sets how often a conversion of corrupt input is retried with a fresh download,
+    /// overriding `RetryPolicy::max_retries`
+    pub fn conversion_retries(mut self, retries: u32) -> Self {
+        self.retry_policy.conversion_retries = Some(retries);
+        self
+    }
+
+    /// Lets `stream_video` download up to `bytes` ahead of the consumer
+    ///
+    /// Smooths out relays to players that read in bursts, while a reader that stops altogether
+    /// holds at most `bytes` in memory. Prefetching is disabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .prefetch_bytes(8 * 1024 * 1024)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn prefetch_bytes(mut self, bytes: usize) -> Self {
+        self.prefetch_bytes = Some(bytes);
+        self
+    }
+
+    /// Downloads media as `chunks` byte ranges in parallel instead of a single request
+    ///
+    /// Servers that don't support ranges, resumed downloads and paused downloads fall back to a
+    /// single request. Defaults to 1, which disables chunking.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .parallel_chunks(16)
+    ///     .max_connections_per_download(4)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn parallel_chunks(mut self, chunks: u8) -> Self {
+        self.parallel_chunks = chunks;
+        self
+    }
+
+    /// Caps how many chunk requests of one download are in flight at once
+    ///
+    /// Every chunk is still downloaded, later chunks wait for a free connection. Defaults to
+    /// the number of `parallel_chunks`.
+    pub fn max_connections_per_download(mut self, connections: u8) -> Self {
+        self.max_connections_per_download = Some(connections);
+        self
+    }
+
+    /// Sets the solver used for `signatureCipher` formats and throttled `n` parameters
+    ///
+    /// Without a solver, ciphered formats have no URL and are skipped by format selection. See
+    /// `SignatureSolver` for an example.
+    pub fn signature_solver(mut self, solver: impl SignatureSolver + 'static) -> Self {
+        self.signature_solver = Some(Arc::new(solver));
+        self
+    }
+
+    /// Reports `ProgressEvent::Downloading` at most once per `interval`
+    ///
+    /// Intermediate updates are coalesced, every byte is still counted and the final state of
+    /// a download is always reported. Handy when progress is relayed over the network. By
+    /// default every chunk is reported.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .progress_interval(Duration::from_millis(250))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = Some(interval);
+        self
+    }
+
+    /// Answers player requests for `video_id` with a pre-recorded response
+    ///
+    /// Format selection and every other decision then runs offline against `response`, while
+    /// media downloads still go to whatever URLs it contains.
+    ///
+    /// # Example
+    /// ```
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let response = serde_json::json!({ "streamingData": { "formats": [] } });
+    /// let downloader = YouTubeDownloader::builder()
+    ///     .replay_response("dQw4w9WgXcQ", response)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "replay")]
+    pub fn replay_response(mut self, video_id: impl Into<String>, response: serde_json::Value) -> Self {
+        self.replay.responses.insert(video_id.into(), response);
+        self
+    }
+
+    /// Answers player requests from `<dir>/<video_id>.json` files where they exist
+    ///
+    /// IDs without a recorded file still go to the network.
+    #[cfg(feature = "replay")]
+    pub fn replay_dir(mut self, dir: PathBuf) -> Self {
+        self.replay.replay_dir = Some(dir);
+        self
+    }
+
+    /// Saves every live player response to `<dir>/<video_id>.json` for later replay
+    #[cfg(feature = "replay")]
+    pub fn record_dir(mut self, dir: PathBuf) -> Self {
+        self.replay.record_dir = Some(dir);
+        self
+    }
+
+    /// Builds the `YouTubeDownloader`
+    pub fn build(self) -> Result<YouTubeDownloader> {
+        let mut client = self.client;
+        for path in &self.root_certificate_files {
+            for certificate in load_certificates(path)? {
+                client = client.add_root_certificate(certificate);
+            }
+        }
+        let client = client.build().map_err(Error::Client)?;
+        Ok(YouTubeDownloader {
+            client,
+            dedupe_store: self.dedupe_store,
+            metrics_hook: self.metrics_hook,
+            on_progress: self.on_progress,
+            resume: self.resume,
+            url_rewriter: self.url_rewriter,
+            retry_policy: self.retry_policy,
+            prefetch_bytes: self.prefetch_bytes,
+            parallel_chunks: self.parallel_chunks,
+            max_connections_per_download: self.max_connections_per_download,
+            signature_solver: self.signature_solver,
+            progress_interval: self.progress_interval,
+            archive: self.archive.map(|path| Arc::new(Archive::new(path))),
+            range_chunk_size: self.range_chunk_size,
+            referer: self.referer,
+            origin: self.origin,
+            max_file_size: self.max_file_size,
+            disk_budget: self.disk_budget,
+            disk_used: Default::default(),
+            shutdown_state: Default::default(),
+            slow_start: self.slow_start,
+            on_retry: self.on_retry,
+            rate_governor: self.rate_governor,
+            download_strategy: self.download_strategy,
+            client_version: match self.auto_update_client_version {
+                true => Some(Default::default()),
+                false => None,
+            },
+            dedup_index: self.dedup_index.map(|path| Arc::new(DedupIndex::new(path))),
+            compress_api_responses: self.compress_api_responses,
+            compress_media: self.compress_media,
+            player_endpoints: self.player_endpoints,
+            request_jitter: self.request_jitter,
+            read_stall_timeout: self.read_stall_timeout,
+            #[cfg(feature = "replay")]
+            replay: self.replay,
+        })
+    }
+}
+
+/// Reads the certificates of a PEM bundle, or the single certificate of a DER file
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let bytes = std::fs::read(path)?;
+    if bytes.windows(11).any(|window| window == b"-----BEGIN ") {
+        Ok(Certificate::from_pem_bundle(&bytes)?)
+    } else {
+        Ok(vec![Certificate::from_der(&bytes)?])
+    }
+}
@@ -0,0 +1,243 @@
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::{ Error, Result, YouTubeDownloader };
+
+/// A caption track listed in the player response
+#[derive(Debug, Clone)]
+pub struct CaptionTrack {
+    /// The language code of the track (e.g. "en" or "de")
+    pub language_code: String,
+    /// The human readable track name (e.g. "English (auto-generated)")
+    pub name: String,
+    /// The timed-text URL of the track
+    pub base_url: String,
+    /// Whether the track was generated by speech recognition
+    pub auto_generated: bool,
+    /// Whether YouTube can machine translate the track into other languages
+    pub translatable: bool,
+}
+
+/// The format captions are downloaded in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CaptionFormat {
+    /// WebVTT
+    #[default]
+    Vtt,
+    /// Timed Text Markup Language
+    Ttml,
+    /// YouTube's own XML format with word-level timing
+    Srv3,
+    /// YouTube's own JSON format with word-level timing
+    Json3,
+}
+
+impl CaptionFormat {
+    /// Returns the value of the timed-text `fmt` parameter
+    fn param(&self) -> &'static str {
+        match self {
+            Self::Vtt => "vtt",
+            Self::Ttml => "ttml",
+            Self::Srv3 => "srv3",
+            Self::Json3 => "json3",
+        }
+    }
+
+    /// Returns the file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Vtt => "vtt",
+            Self::Ttml => "ttml",
+            Self::Srv3 => "xml",
+            Self::Json3 => "json",
+        }
+    }
+}
+
+impl YouTubeDownloader {
+    /// Lists the caption tracks available for a YouTube video ID
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for track in downloader.list_captions("dQw4w9WgXcQ").await? {
+    ///     println!("{} {}", track.language_code, track.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_captions(&self, video_id: &str) -> Result<Vec<CaptionTrack>> {
+        let json = self.get_player_response(video_id).await?;
+        Ok(parse_caption_tracks(&json))
+    }
+
+    /// Downloads the caption track of a language, returning the path of the written file
+    ///
+    /// The file is named `<video_id>.<language>.<extension>`.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `language` - The language code of the track (e.g. "en")
+    /// * `format` - The caption format to download
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ CaptionFormat, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let path = downloader.download_caption("dQw4w9WgXcQ", "en", CaptionFormat::Vtt).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_caption(&self, video_id: &str, language: &str, format: CaptionFormat) -> Result<String> {
+        let track = self.find_caption_track(video_id, language).await?;
+        let path = format!("{video_id}.{language}.{}", format.extension());
+        self.download_caption_url(&track.base_url, format, None, &path).await
+    }
+
+    /// Downloads a caption track machine translated by YouTube into another language
+    ///
+    /// The source track has to exist and be translatable, otherwise `Error::Api` is returned.
+    /// The file is named `<video_id>.<target_lang>.<extension>`.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `source_lang` - The language code of the track to translate (e.g. "en")
+    /// * `target_lang` - The language code to translate into (e.g. "de")
+    /// * `format` - The caption format to download
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ CaptionFormat, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let path = downloader
+    ///     .download_caption_translated("dQw4w9WgXcQ", "en", "de", CaptionFormat::Vtt)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_caption_translated(
+        &self,
+        video_id: &str,
+        source_lang: &str,
+        target_lang: &str,
+        format: CaptionFormat
+    ) -> Result<String> {
+        let track = self.find_caption_track(video_id, source_lang).await?;
+        if !track.translatable {
+            return Err(Error::Api(format!("The {source_lang} caption track can't be translated")));
+        }
+
+        let path = format!("{video_id}.{target_lang}.{}", format.extension());
+        self.download_caption_url(&track.base_url, format, Some(target_lang), &path).await
+    }
+
+    /// Downloads the caption track of a language into memory instead of a file
+    ///
+    /// Convenient for web handlers serving captions straight from a response.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `language` - The language code of the track (e.g. "en")
+    /// * `format` - The caption format to download
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ CaptionFormat, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let vtt = downloader.download_caption_to_vec("dQw4w9WgXcQ", "en", CaptionFormat::Vtt).await?;
+    /// println!("{}", String::from_utf8_lossy(&vtt));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_caption_to_vec(
+        &self,
+        video_id: &str,
+        language: &str,
+        format: CaptionFormat
+    ) -> Result<Vec<u8>> {
+        let track = self.find_caption_track(video_id, language).await?;
+        self.fetch_caption(&track.base_url, format, None).await
+    }
+
+    async fn find_caption_track(&self, video_id: &str, language: &str) -> Result<CaptionTrack> {
+        self
+            .list_captions(video_id).await?
+            .into_iter()
+            .find(|track| track.language_code == language)
+            .ok_or_else(|| Error::Api(format!("No caption track found for language {language}")))
+    }
+
+    pub(crate) async fn download_caption_url(
+        &self,
+        base_url: &str,
+        format: CaptionFormat,
+        translate_to: Option<&str>,
+        path: &str
+    ) -> Result<String> {
+        let caption = self.fetch_caption(base_url, format, translate_to).await?;
+        tokio::fs::write(path, caption).await?;
+        Ok(path.to_string())
+    }
+
+    async fn fetch_caption(
+        &self,
+        base_url: &str,
+        format: CaptionFormat,
+        translate_to: Option<&str>
+    ) -> Result<Vec<u8>> {
+        let mut url = Url::parse(base_url).map_err(|e| Error::Api(e.to_string()))?;
+        // The base URL may already carry a format, which would win over an appended one
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "fmt" && key != "tlang")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        {
+            let mut query = url.query_pairs_mut();
+            query.clear().extend_pairs(pairs).append_pair("fmt", format.param());
+            if let Some(target) = translate_to {
+                query.append_pair("tlang", target);
+            }
+        }
+
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(Error::Api(format!("Caption request failed with status: {}", response.status())));
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+pub(crate) fn parse_caption_tracks(json: &Value) -> Vec<CaptionTrack> {
+    json["captions"]["playerCaptionsTracklistRenderer"]["captionTracks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|track| {
+            Some(CaptionTrack {
+                language_code: track["languageCode"].as_str()?.to_string(),
+                name: track["name"]["simpleText"]
+                    .as_str()
+                    .or_else(|| track["name"]["runs"][0]["text"].as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                base_url: track["baseUrl"].as_str()?.to_string(),
+                auto_generated: track["kind"].as_str() == Some("asr"),
+                translatable: track["isTranslatable"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,153 @@
+use serde_json::Value;
+use std::path::Path;
+use tokio::fs::{ remove_file, rename };
+
+use crate::{ Result, YouTubeDownloader };
+
+/// A chapter of a video
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    /// The chapter title (e.g. "Intro")
+    pub title: String,
+    /// The start of the chapter in seconds
+    pub start_seconds: u64,
+    /// The end of the chapter in seconds, which is the start of the next chapter
+    pub end_seconds: u64,
+}
+
+impl YouTubeDownloader {
+    /// Extracts the chapters of a video from the timestamps in its description
+    ///
+    /// YouTube only shows chapters for descriptions listing at least three ascending timestamps
+    /// starting at 0:00, the same rule is applied here. Returns an empty list otherwise.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for chapter in downloader.get_chapters("dQw4w9WgXcQ").await? {
+    ///     println!("{}s {}", chapter.start_seconds, chapter.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_chapters(&self, video_id: &str) -> Result<Vec<Chapter>> {
+        let json = self.get_player_response(video_id).await?;
+        Ok(parse_chapters(&json))
+    }
+
+    /// Writes chapters into the metadata of `path` with FFmpeg, without re-encoding
+    pub(crate) async fn embed_chapters(&self, path: &str, chapters: &[Chapter]) -> Result<()> {
+        let path = Path::new(path);
+        let metadata_path = path.with_extension("chapters.txt");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+        let tagged_path = path.with_extension(format!("chapters.{extension}")).to_string_lossy().into_owned();
+
+        tokio::fs::write(&metadata_path, ffmetadata(chapters)).await?;
+        let mut args = vec!["-y".to_string(), "-i".to_string(), path.to_string_lossy().into_owned()];
+        args.extend(["-i".to_string(), metadata_path.to_string_lossy().into_owned()]);
+        // Global tags come from the file itself, only the chapters from the ffmetadata input
+        args.extend(
+            ["-map", "0", "-map_metadata", "0", "-map_chapters", "1", "-codec", "copy"].into_iter().map(String::from)
+        );
+        args.push(tagged_path.clone());
+        let result = Self::run_ffmpeg(args, Some(&tagged_path), None).await;
+        remove_file(&metadata_path).await?;
+        if let Err(e) = result {
+            let _ = remove_file(&tagged_path).await;
+            return Err(e);
+        }
+
+        rename(&tagged_path, path).await?;
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_chapters(json: &Value) -> Vec<Chapter> {
+    let details = &json["videoDetails"];
+    let description = details["shortDescription"].as_str().unwrap_or_default();
+    let length: u64 = details["lengthSeconds"]
+        .as_str()
+        .and_then(|l| l.parse().ok())
+        .unwrap_or(0);
+
+    let mut starts: Vec<(u64, String)> = Vec::new();
+    for line in description.lines() {
+        let Some((start, title)) = parse_chapter_line(line) else {
+            continue;
+        };
+        if starts.last().is_some_and(|(last, _)| start <= *last) {
+            continue;
+        }
+        starts.push((start, title));
+    }
+
+    if starts.len() < 3 || starts[0].0 != 0 {
+        return Vec::new();
+    }
+
+    let ends: Vec<u64> = starts
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(std::iter::once(length.max(starts[starts.len() - 1].0)))
+        .collect();
+    starts
+        .into_iter()
+        .zip(ends)
+        .map(|((start_seconds, title), end_seconds)| Chapter { title, start_seconds, end_seconds })
+        .collect()
+}
+
+/// Parses lines such as "1:02:03 Title", "0:00 - Intro" or "12:30: Outro"
+fn parse_chapter_line(line: &str) -> Option<(u64, String)> {
+    let line = line.trim();
+    let (timestamp, title) = line.split_once(char::is_whitespace)?;
+    let timestamp = timestamp.trim_end_matches(':');
+
+    let parts: Vec<u64> = timestamp
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let seconds = match parts[..] {
+        [minutes, seconds] if seconds < 60 => minutes * 60 + seconds,
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => hours * 3600 + minutes * 60 + seconds,
+        _ => {
+            return None;
+        }
+    };
+
+    let title = title.trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == '–' || c == ':');
+    (!title.is_empty()).then(|| (seconds, title.trim_end().to_string()))
+}
+
+/// Renders chapters in FFmpeg's metadata file format
+pub(crate) fn ffmetadata(chapters: &[Chapter]) -> String {
+    let escape = |text: &str| {
+        text.chars().fold(String::new(), |mut escaped, c| {
+            if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+    };
+
+    let mut metadata = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        metadata.push_str(
+            &format!(
+                "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+                chapter.start_seconds * 1000,
+                chapter.end_seconds * 1000,
+                escape(&chapter.title)
+            )
+        );
+    }
+    metadata
+}
@@ -0,0 +1,318 @@
+use futures_util::{ stream, StreamExt, TryStreamExt };
+use indicatif::ProgressBar;
+use reqwest::{ StatusCode, Url, header };
+use std::{ io::SeekFrom, sync::atomic::{ AtomicU64, Ordering }, time::{ Duration, Instant } };
+use tokio::{ fs::{ File, OpenOptions, remove_file }, io::{ AsyncSeekExt, AsyncWriteExt } };
+
+use crate::{
+    fifo,
+    pacing::SlowStart,
+    progress::ProgressThrottle,
+    DownloadControl,
+    DownloadProgress,
+    Error,
+    ProgressEvent,
+    ResumeOutcome,
+    Result,
+    TransferEvent,
+    YouTubeDownloader,
+};
+
+/// How downloads request their bytes
+///
+/// # Example
+/// ```
+/// use rustytdown::{ DownloadStrategy, YouTubeDownloader };
+///
+/// let downloader = YouTubeDownloader::builder()
+///     .download_strategy(DownloadStrategy::Ranged { chunk_size: 4 * 1024 * 1024 })
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DownloadStrategy {
+    /// A single GET, split into parallel ranges with `parallel_chunks` and into
+    /// `range_chunk_size` requests for googlevideo URLs that need it
+    #[default]
+    Auto,
+    /// Always fetches the file sequentially in ranges of `chunk_size` bytes, appended in
+    /// order, for servers that cap the bytes of a single response
+    ///
+    /// Throttled googlevideo URLs get the range as `&range=` parameter, other URLs as `Range`
+    /// header.
+    Ranged {
+        chunk_size: u64,
+    },
+}
+
+impl YouTubeDownloader {
+    /// Downloads `url` to `path` as `parallel_chunks` byte ranges, returning the time to first
+    /// byte, or `None` if the server doesn't support ranges and the caller should fall back
+    ///
+    /// At most `max_connections_per_download` ranges are requested at once, so the chunk
+    /// granularity doesn't dictate how many sockets are opened.
+    pub(crate) async fn download_chunked(&self, url: &str, path: &str, pb: &ProgressBar) -> Result<Option<Duration>> {
+        let ttfb_start = Instant::now();
+        let probe = self
+            .media_request(url)
+            .header(header::RANGE, "bytes=0-0")
+            .send().await?;
+        let total_size = match probe.status() {
+            StatusCode::PARTIAL_CONTENT => content_range_total(&probe),
+            _ => None,
+        };
+        let Some(total_size) = total_size.filter(|size| *size > 0) else {
+            return Ok(None);
+        };
+
+        let ttfb = ttfb_start.elapsed();
+        println!("Time to First Byte: {:.2?}", ttfb);
+        self.emit(TransferEvent::FirstByte { path: path.to_string(), ttfb });
+
+        File::create(path).await?.set_len(total_size).await?;
+        pb.set_length(total_size);
+        pb.set_position(0);
+
+        let chunks = u64::from(self.parallel_chunks.max(1));
+        let chunk_size = total_size.div_ceil(chunks);
+        let ranges = (0..chunks)
+            .map(|i| (i * chunk_size, ((i + 1) * chunk_size).min(total_size) - 1))
+            .filter(|(start, end)| start <= end);
+        let connections = self.max_connections_per_download.unwrap_or(self.parallel_chunks).max(1);
+
+        let progress = ChunkProgress {
+            downloaded: AtomicU64::new(0),
+            total_size,
+            throttle: ProgressThrottle::new(self.progress_interval),
+            pb,
+        };
+        let result = stream::iter(ranges)
+            .map(|range| self.download_range(url, path, range, &progress))
+            .buffer_unordered(usize::from(connections))
+            .try_collect::<Vec<()>>().await;
+        // The file is preallocated to its full size, which a resuming download would take as complete
+        if let Err(e) = result {
+            let _ = remove_file(path).await;
+            return Err(e);
+        }
+        if progress.throttle.take_pending() {
+            self.report(
+                ProgressEvent::Downloading(DownloadProgress::new(total_size, Some(total_size), progress.pb))
+            );
+        }
+
+        self.emit(TransferEvent::Completed {
+            path: path.to_string(),
+            bytes: total_size,
+            duration: ttfb_start.elapsed(),
+            resume: ResumeOutcome::Fresh,
+        });
+        Ok(Some(ttfb))
+    }
+
+    async fn download_range(
+        &self,
+        url: &str,
+        path: &str,
+        (start, end): (u64, u64),
+        progress: &ChunkProgress<'_>
+    ) -> Result<()> {
+        let res = self
+            .media_request(url)
+            .header(header::RANGE, format!("bytes={start}-{end}"))
+            .send().await?;
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(Error::Api(format!("Range {start}-{end} failed with status: {}", res.status())));
+        }
+
+        let mut file = OpenOptions::new().write(true).open(path).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            self.govern(chunk.len() as u64).await;
+            progress.pb.inc(chunk.len() as u64);
+            let done = progress.downloaded.fetch_add(chunk.len() as u64, Ordering::Relaxed) + (chunk.len() as u64);
+            if progress.throttle.ready() {
+                self.report(
+                    ProgressEvent::Downloading(DownloadProgress::new(done, Some(progress.total_size), progress.pb))
+                );
+            }
+        }
+        file.flush().await?;
+        Ok(())
+    }
+
+    /// Downloads `url` to `path` as consecutive range requests of `range_chunk_size` bytes,
+    /// or the chunk size of `DownloadStrategy::Ranged`, returning the time to first byte
+    ///
+    /// Throttled googlevideo URLs take the range as `&range=start-end` and don't report the total
+    /// size, so chunks are requested until the format size is reached or a chunk comes back
+    /// short. Other URLs get a `Range` header, whose `Content-Range` answer carries the
+    /// total. Starts at `offset` when resuming, and pauses or cancels between chunks.
+    pub(crate) async fn download_ranged(
+        &self,
+        url: &str,
+        path: &str,
+        pb: &ProgressBar,
+        offset: u64,
+        control: Option<&DownloadControl>,
+        format_size: Option<u64>
+    ) -> Result<Duration> {
+        let ttfb_start = Instant::now();
+        let mut file = match offset {
+            0 => fifo::create_output(path).await?,
+            _ => OpenOptions::new().append(true).open(path).await?,
+        };
+        let resume = match offset {
+            0 => ResumeOutcome::Fresh,
+            offset => ResumeOutcome::Appended { offset },
+        };
+        pb.set_length(format_size.unwrap_or(0));
+        pb.set_position(offset);
+
+        let chunk_size = match self.download_strategy {
+            DownloadStrategy::Ranged { chunk_size } => chunk_size.max(1),
+            DownloadStrategy::Auto => self.range_chunk_size,
+        };
+        let by_header = !is_range_chunked(url);
+        let throttle = ProgressThrottle::new(self.progress_interval);
+        let mut slow_start = self.slow_start.map(SlowStart::new);
+        let mut total = format_size;
+        let mut header_total = None;
+        let mut position = offset;
+        let mut ttfb = None;
+        loop {
+            if total.is_some_and(|size| position >= size) {
+                break;
+            }
+            if let Some(control) = control {
+                control.wait_while_paused().await;
+                if control.is_cancelled() {
+                    drop(file);
+                    fifo::discard_output(path).await?;
+                    return Err(Error::Cancelled);
+                }
+            }
+
+            let end = match total {
+                Some(size) => (position + chunk_size).min(size) - 1,
+                None => position + chunk_size - 1,
+            };
+            let request = match by_header {
+                true => self.media_request(url).header(header::RANGE, format!("bytes={position}-{end}")),
+                false => self.media_request(with_range(url, position, end)?.as_str()),
+            };
+            let res = request.send().await?;
+            // A server ignoring the Range header sends the whole file, which is fine only at the start
+            let whole_file = by_header && res.status() == StatusCode::OK;
+            if !res.status().is_success() || (whole_file && position > 0) {
+                return Err(Error::Api(format!("Range {position}-{end} failed with status: {}", res.status())));
+            }
+            if by_header && header_total.is_none() {
+                header_total = content_range_total(&res);
+                if total.is_none() && header_total.is_some() {
+                    total = header_total;
+                    pb.set_length(total.unwrap_or(0));
+                }
+            }
+            if ttfb.is_none() {
+                let elapsed = ttfb_start.elapsed();
+                println!("Time to First Byte: {:.2?}", elapsed);
+                self.emit(TransferEvent::FirstByte { path: path.to_string(), ttfb: elapsed });
+                ttfb = Some(elapsed);
+            }
+
+            let mut stream = res.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                file.write_all(&chunk).await?;
+                position += chunk.len() as u64;
+                if let Some(delay) = slow_start.as_mut().and_then(|pacing| pacing.delay(chunk.len() as u64)) {
+                    tokio::time::sleep(delay).await;
+                }
+                self.govern(chunk.len() as u64).await;
+                pb.set_position(position);
+                if throttle.ready() {
+                    self.report(
+                        ProgressEvent::Downloading(DownloadProgress::new(position, total, pb))
+                    );
+                }
+            }
+
+            // A short chunk is the last one
+            if whole_file || position <= end {
+                break;
+            }
+        }
+        file.flush().await?;
+
+        if throttle.take_pending() {
+            self.report(
+                ProgressEvent::Downloading(DownloadProgress::new(position, total, pb))
+            );
+        }
+        crate::check_size(path, position, format_size, header_total)?;
+
+        self.emit(TransferEvent::Completed {
+            path: path.to_string(),
+            bytes: position - offset,
+            duration: ttfb_start.elapsed(),
+            resume,
+        });
+        Ok(ttfb.unwrap_or_else(|| ttfb_start.elapsed()))
+    }
+}
+
+/// Returns true for googlevideo URLs that only serve small chunks to a plain GET
+///
+/// Streams without `ratebypass=yes`, and URLs already carrying a `range` parameter, are
+/// throttled or cut off unless requested in explicit ranges.
+pub(crate) fn is_range_chunked(url: &str) -> bool {
+    let Ok(url) = Url::parse(url) else {
+        return false;
+    };
+    if !url.host_str().is_some_and(|host| host.ends_with(".googlevideo.com")) {
+        return false;
+    }
+    let has_range = url.query_pairs().any(|(key, _)| key == "range");
+    let rate_bypass = url.query_pairs().any(|(key, value)| key == "ratebypass" && value == "yes");
+    has_range || !rate_bypass
+}
+
+/// Replaces the `range` parameter of `url` with `start-end`
+fn with_range(url: &str, start: u64, end: u64) -> Result<Url> {
+    let mut url = Url::parse(url).map_err(|e| Error::Api(e.to_string()))?;
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "range")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("range", &format!("{start}-{end}"));
+    Ok(url)
+}
+
+/// Progress shared by the concurrent ranges of one download
+struct ChunkProgress<'a> {
+    downloaded: AtomicU64,
+    total_size: u64,
+    throttle: ProgressThrottle,
+    pb: &'a ProgressBar,
+}
+
+/// Reads the total size out of a `Content-Range: bytes 0-0/12345` header
+fn content_range_total(res: &reqwest::Response) -> Option<u64> {
+    res.headers()
+        .get(header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit('/')
+        .next()?
+        .parse()
+        .ok()
+}
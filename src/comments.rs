@@ -0,0 +1,180 @@
+use serde_json::Value;
+
+use crate::{ Error, InnertubeClient, Result, YouTubeDownloader };
+
+/// A top-level comment on a video
+#[derive(Debug, Clone)]
+pub struct Comment {
+    /// The comment ID
+    pub id: String,
+    /// The author's display name (e.g. "@RickAstleyYT")
+    pub author: String,
+    /// The comment text
+    pub text: String,
+    /// The like count, parsed from YouTube's abbreviated form (e.g. "1.2K" becomes 1200)
+    pub like_count: u64,
+    /// The publish time as shown by YouTube (e.g. "2 years ago")
+    pub published_time: String,
+}
+
+impl YouTubeDownloader {
+    /// Fetches up to `max` top-level comments of a video
+    ///
+    /// Comments are returned in the order YouTube serves them, following continuation tokens
+    /// of the `next` endpoint until `max` comments were collected or no pages are left.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `max` - The maximum number of comments to return
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for comment in downloader.get_comments("dQw4w9WgXcQ", 100).await? {
+    ///     println!("{}: {}", comment.author, comment.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_comments(&self, video_id: &str, max: usize) -> Result<Vec<Comment>> {
+        let mut comments = Vec::new();
+        if max == 0 {
+            return Ok(comments);
+        }
+
+        let next = self.innertube_request(
+            InnertubeClient::Web,
+            "next",
+            serde_json::json!({ "videoId": video_id })
+        ).await?;
+
+        let mut token = find_comments_token(&next).ok_or_else(||
+            Error::Api("No comment section found, comments may be disabled".into())
+        )?;
+
+        loop {
+            let page = self.innertube_request(
+                InnertubeClient::Web,
+                "next",
+                serde_json::json!({ "continuation": token })
+            ).await?;
+
+            let mut next_token = None;
+            for item in continuation_items(&page) {
+                if let Some(comment) = parse_comment_thread(item, &page) {
+                    comments.push(comment);
+                } else if let Some(t) = continuation_token(item) {
+                    next_token = Some(t);
+                }
+            }
+
+            if comments.len() >= max {
+                comments.truncate(max);
+                break;
+            }
+            match next_token {
+                Some(t) => {
+                    token = t;
+                }
+                None => {
+                    break;
+                }
+            }
+        }
+
+        Ok(comments)
+    }
+}
+
+fn find_comments_token(next: &Value) -> Option<String> {
+    next["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"]
+        .as_array()?
+        .iter()
+        .filter(|section| {
+            section["itemSectionRenderer"]["sectionIdentifier"].as_str() == Some("comment-item-section")
+        })
+        .flat_map(|section| section["itemSectionRenderer"]["contents"].as_array().into_iter().flatten())
+        .find_map(continuation_token)
+}
+
+fn continuation_items(page: &Value) -> impl Iterator<Item = &Value> {
+    page["onResponseReceivedEndpoints"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|endpoint| {
+            endpoint["reloadContinuationItemsCommand"]["continuationItems"]
+                .as_array()
+                .or_else(|| endpoint["appendContinuationItemsAction"]["continuationItems"].as_array())
+        })
+        .flatten()
+}
+
+fn continuation_token(item: &Value) -> Option<String> {
+    let renderer = &item["continuationItemRenderer"];
+    renderer["continuationEndpoint"]["continuationCommand"]["token"]
+        .as_str()
+        .or_else(|| renderer["button"]["buttonRenderer"]["command"]["continuationCommand"]["token"].as_str())
+        .map(String::from)
+}
+
+fn parse_comment_thread(item: &Value, page: &Value) -> Option<Comment> {
+    let thread = item.get("commentThreadRenderer")?;
+
+    // Older responses inline the comment, newer ones reference an entity in frameworkUpdates
+    if let Some(renderer) = thread["comment"].get("commentRenderer") {
+        return Some(Comment {
+            id: renderer["commentId"].as_str()?.to_string(),
+            author: text_of(&renderer["authorText"]),
+            text: text_of(&renderer["contentText"]),
+            like_count: parse_count(renderer["voteCount"]["simpleText"].as_str().unwrap_or("0")),
+            published_time: text_of(&renderer["publishedTimeText"]),
+        });
+    }
+
+    let key = thread["commentViewModel"]["commentViewModel"]["commentKey"].as_str()?;
+    let payload = page["frameworkUpdates"]["entityBatchUpdate"]["mutations"]
+        .as_array()?
+        .iter()
+        .find(|mutation| mutation["entityKey"].as_str() == Some(key))?
+        .get("payload")?
+        .get("commentEntityPayload")?;
+
+    Some(Comment {
+        id: payload["properties"]["commentId"].as_str()?.to_string(),
+        author: payload["author"]["displayName"].as_str().unwrap_or_default().to_string(),
+        text: payload["properties"]["content"]["content"].as_str().unwrap_or_default().to_string(),
+        like_count: parse_count(payload["toolbar"]["likeCountNotliked"].as_str().unwrap_or("0")),
+        published_time: payload["properties"]["publishedTime"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Joins the `runs` of a text object or returns its `simpleText`
+fn text_of(text: &Value) -> String {
+    match text["runs"].as_array() {
+        Some(runs) =>
+            runs
+                .iter()
+                .filter_map(|run| run["text"].as_str())
+                .collect(),
+        None => text["simpleText"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Parses abbreviated counts such as "937", "1.2K" or "3M"
+fn parse_count(count: &str) -> u64 {
+    let count = count.trim().replace(',', "");
+    let (number, multiplier) = match count.chars().last() {
+        Some('K') => (&count[..count.len() - 1], 1_000.0),
+        Some('M') => (&count[..count.len() - 1], 1_000_000.0),
+        Some('B') => (&count[..count.len() - 1], 1_000_000_000.0),
+        _ => (count.as_str(), 1.0),
+    };
+    number
+        .parse::<f64>()
+        .map(|n| (n * multiplier).round() as u64)
+        .unwrap_or(0)
+}
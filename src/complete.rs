@@ -0,0 +1,339 @@
+use std::{ collections::HashMap, path::{ Path, PathBuf }, time::{ Instant, SystemTime } };
+use tokio::fs::{ remove_file, rename };
+
+use crate::{
+    batch::{ self, Artifact },
+    captions,
+    chapters,
+    format,
+    CaptionFormat,
+    DownloadReport,
+    Error,
+    FormatPreference,
+    Result,
+    SourceKind,
+    StreamKind,
+    VideoInfo,
+    YouTubeDownloader,
+};
+
+/// What `YouTubeDownloader::download_complete` puts into and next to the merged file
+///
+/// Everything except subtitles is enabled by default. Artifacts are named by the same
+/// templates as `BatchOptions`.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ Artifact, CompleteOptions, FormatPreference };
+///
+/// let options = CompleteOptions::new("archive")
+///     .preference(FormatPreference::default().max_height(1080))
+///     .subtitles(["en", "de"])
+///     .template(Artifact::Media, "{title} [{id}].{ext}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompleteOptions {
+    out_dir: PathBuf,
+    preference: FormatPreference,
+    metadata: bool,
+    cover_art: bool,
+    chapters: bool,
+    subtitles: Vec<String>,
+    info_json: bool,
+    description: bool,
+    templates: HashMap<Artifact, String>,
+}
+
+impl CompleteOptions {
+    /// Creates options writing into `out_dir`
+    pub fn new(out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+            preference: FormatPreference::default(),
+            metadata: true,
+            cover_art: true,
+            chapters: true,
+            subtitles: Vec::new(),
+            info_json: true,
+            description: false,
+            templates: HashMap::new(),
+        }
+    }
+
+    /// Sets the constraints for the video stream, its stream kind is ignored
+    pub fn preference(mut self, preference: FormatPreference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    /// Embeds the title, channel, description, source URL and download date as tags
+    ///
+    /// The URL (`https://youtu.be/<id>`) goes into the `comment` and `purl` tags and the
+    /// download time into `date`, as a UTC ISO 8601 timestamp, so files stay traceable to
+    /// their source without a sidecar.
+    pub fn metadata(mut self, enabled: bool) -> Self {
+        self.metadata = enabled;
+        self
+    }
+
+    /// Embeds the thumbnail as cover art, skipped for WebM which can't carry one
+    pub fn cover_art(mut self, enabled: bool) -> Self {
+        self.cover_art = enabled;
+        self
+    }
+
+    /// Embeds the chapters of the video
+    pub fn chapters(mut self, enabled: bool) -> Self {
+        self.chapters = enabled;
+        self
+    }
+
+    /// Embeds the caption tracks of these languages as soft subtitles, skipping languages
+    /// that aren't offered
+    pub fn subtitles<I, S>(mut self, languages: I) -> Self where I: IntoIterator<Item = S>, S: Into<String> {
+        self.subtitles = languages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Writes the raw `videoDetails` and the offered caption tracks next to the media
+    pub fn info_json(mut self, enabled: bool) -> Self {
+        self.info_json = enabled;
+        self
+    }
+
+    /// Writes the description text to a `.description` sidecar next to the media
+    pub fn description(mut self, enabled: bool) -> Self {
+        self.description = enabled;
+        self
+    }
+
+    /// Sets the output path template of an artifact type, as `BatchOptions::template`
+    pub fn template(mut self, artifact: Artifact, template: impl Into<String>) -> Self {
+        self.templates.insert(artifact, template.into());
+        self
+    }
+}
+
+impl YouTubeDownloader {
+    /// Downloads a video in the best quality as one fully tagged file
+    ///
+    /// Selects and merges the best video-only and audio-only streams, then embeds metadata,
+    /// cover art, chapters and soft subtitles in a single FFmpeg pass, and writes the info
+    /// JSON next to it. The turnkey variant of `download_merged` for archiving.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `opts` - What to embed and where to write
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ CompleteOptions, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let report = downloader
+    ///     .download_complete("dQw4w9WgXcQ", CompleteOptions::new(".").subtitles(["en"]))
+    ///     .await?;
+    /// println!("Saved {}", report.path);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_complete(&self, video_id: &str, opts: CompleteOptions) -> Result<DownloadReport> {
+        let start_time = Instant::now();
+        self.check_archive(video_id).await?;
+        let json = self.get_player_response(video_id).await?;
+        let info = VideoInfo::from_player_response(&json)?;
+
+        let formats = format::parse_formats(&json);
+        let video = opts.preference
+            .clone()
+            .with_kind(StreamKind::VideoOnly)
+            .select(&formats)
+            .ok_or_else(|| Error::Api("No video-only stream satisfies the requested preference".into()))?;
+        let audio = FormatPreference::new(StreamKind::AudioOnly)
+            .select(&formats)
+            .ok_or_else(|| Error::Api("No audio-only stream found".into()))?;
+
+        let title = batch::sanitize_file_name(&info.title).unwrap_or_else(|| video_id.to_string());
+        let extension = format::merged_extension(video, audio);
+        let vars = [("id", video_id), ("title", title.as_str()), ("ext", extension)];
+        tokio::fs::create_dir_all(&opts.out_dir).await?;
+        let media_path = batch::artifact_path(&opts.templates, Artifact::Media, &opts.out_dir, &vars).await?;
+
+        if opts.info_json {
+            let path = batch::artifact_path(&opts.templates, Artifact::Info, &opts.out_dir, &vars[..2]).await?;
+            tokio::fs::write(path, batch::info_sidecar(&json)?).await?;
+        }
+
+        if opts.description {
+            let path = batch::artifact_path(&opts.templates, Artifact::Description, &opts.out_dir, &vars[..2]).await?;
+            tokio::fs::write(path, &info.description).await?;
+        }
+
+        let path = self.merge_formats(video, audio, &media_path).await?;
+
+        let mut embeds = Embeds::default();
+        let result = match self.collect_embeds(&json, &info, &opts, &media_path, extension, &mut embeds).await {
+            Ok(()) if !embeds.args.is_empty() => embed_all(&path, &embeds, extension).await,
+            other => other,
+        };
+        for input in &embeds.inputs {
+            let _ = remove_file(input).await;
+        }
+        result?;
+        self.record_archive(video_id).await?;
+
+        Ok(DownloadReport {
+            path,
+            width: video.width,
+            height: video.height,
+            aspect_ratio: match (video.width, video.height) {
+                (Some(width), Some(height)) if height > 0 => Some((width as f64) / (height as f64)),
+                _ => None,
+            },
+            source_kind: Some(SourceKind::MuxedAdaptive),
+            format: Some(video.clone()),
+            duration: start_time.elapsed(),
+        })
+    }
+
+    /// Fetches the side inputs and builds the FFmpeg arguments embedding them
+    async fn collect_embeds(
+        &self,
+        json: &serde_json::Value,
+        info: &VideoInfo,
+        opts: &CompleteOptions,
+        media_path: &Path,
+        extension: &str,
+        embeds: &mut Embeds
+    ) -> Result<()> {
+        let Embeds { inputs, args } = embeds;
+        let mut maps: Vec<String> = vec!["-map".into(), "0".into()];
+        let mut codecs: Vec<String> = vec!["-c".into(), "copy".into()];
+
+        if opts.metadata {
+            let url = format!("https://youtu.be/{}", info.video_id);
+            let date = utc_timestamp(SystemTime::now());
+            for (key, value) in [
+                ("title", info.title.as_str()),
+                ("artist", info.author.as_str()),
+                ("description", info.description.as_str()),
+                ("comment", url.as_str()),
+                ("purl", url.as_str()),
+                ("date", date.as_str()),
+            ] {
+                args.extend(["-metadata".into(), format!("{key}={value}")]);
+            }
+        }
+
+        if opts.cover_art && extension != "webm" {
+            if let Some(url) = &info.thumbnail_url {
+                let response = self.client.get(url).send().await?;
+                if response.status().is_success() {
+                    let cover = media_path.with_extension("cover");
+                    tokio::fs::write(&cover, response.bytes().await?).await?;
+                    maps.extend(["-map".into(), format!("{}", inputs.len() + 1)]);
+                    codecs.extend(
+                        ["-c:v:1", "mjpeg", "-disposition:v:1", "attached_pic"].into_iter().map(String::from)
+                    );
+                    inputs.push(cover);
+                }
+            }
+        }
+
+        let tracks = captions::parse_caption_tracks(json);
+        let mut subtitle_index = 0;
+        for language in &opts.subtitles {
+            let Some(track) = tracks.iter().find(|track| &track.language_code == language) else {
+                continue;
+            };
+            let subtitle = media_path.with_extension(format!("{language}.vtt"));
+            let subtitle_path = subtitle.to_string_lossy().into_owned();
+            self.download_caption_url(&track.base_url, CaptionFormat::Vtt, None, &subtitle_path).await?;
+            maps.extend(["-map".into(), format!("{}", inputs.len() + 1)]);
+            codecs.extend([
+                format!("-metadata:s:s:{subtitle_index}"),
+                format!("language={language}"),
+            ]);
+            subtitle_index += 1;
+            inputs.push(subtitle);
+        }
+        if subtitle_index > 0 {
+            let codec = match extension {
+                "mp4" => "mov_text",
+                "webm" => "webvtt",
+                _ => "srt",
+            };
+            codecs.extend(["-c:s".into(), codec.into()]);
+        }
+
+        if opts.chapters {
+            let chapters = chapters::parse_chapters(json);
+            if !chapters.is_empty() {
+                let metadata = media_path.with_extension("chapters.txt");
+                tokio::fs::write(&metadata, chapters::ffmetadata(&chapters)).await?;
+                args.extend(["-map_chapters".into(), format!("{}", inputs.len() + 1)]);
+                inputs.push(metadata);
+            }
+        }
+
+        if args.is_empty() && inputs.is_empty() {
+            return Ok(());
+        }
+        args.extend(maps);
+        args.extend(codecs);
+        Ok(())
+    }
+}
+
+/// Side inputs of the tagging pass and the FFmpeg arguments using them
+///
+/// The inputs are temporary files, removed once the pass finished.
+#[derive(Default)]
+struct Embeds {
+    inputs: Vec<PathBuf>,
+    args: Vec<String>,
+}
+
+/// Runs the single FFmpeg pass tagging `path` in place
+async fn embed_all(path: &str, embeds: &Embeds, extension: &str) -> Result<()> {
+    let tagged = Path::new(path).with_extension(format!("tagged.{extension}")).to_string_lossy().into_owned();
+
+    let mut args = vec!["-y".to_string(), "-i".to_string(), path.to_string()];
+    for input in &embeds.inputs {
+        args.extend(["-i".to_string(), input.to_string_lossy().into_owned()]);
+    }
+    args.extend(embeds.args.iter().cloned());
+    args.push(tagged.clone());
+
+    if let Err(e) = YouTubeDownloader::run_ffmpeg(args, Some(&tagged), None).await {
+        let _ = remove_file(&tagged).await;
+        return Err(e);
+    }
+    rename(&tagged, path).await?;
+    Ok(())
+}
+
+/// Formats a time as a UTC ISO 8601 timestamp (e.g. "2024-05-01T12:34:56Z")
+fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = ((seconds / 86_400) as i64, seconds % 86_400);
+
+    // Converts days since the epoch to a civil date, after Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3600,
+        (rest % 3600) / 60,
+        rest % 60
+    )
+}
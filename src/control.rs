@@ -0,0 +1,76 @@
+use std::sync::{ Arc, atomic::{ AtomicBool, Ordering } };
+use tokio::sync::Notify;
+
+/// A handle pausing, resuming and cancelling a running download
+///
+/// Returned by `YouTubeDownloader::download_video_controlled` and
+/// `download_and_convert_controlled` next to the download future. Pausing takes effect after
+/// the chunk being written and closes the connection, resuming reconnects with a Range
+/// request starting at the first missing byte. Cancelling stops the download or kills a
+/// running FFmpeg conversion, removes the partial files and fails with `Error::Cancelled`.
+/// Clones control the same download.
+#[derive(Debug, Clone, Default)]
+pub struct DownloadControl {
+    state: Arc<ControlState>,
+}
+
+#[derive(Debug, Default)]
+struct ControlState {
+    paused: AtomicBool,
+    resumed: Notify,
+    cancelled: AtomicBool,
+    cancel: Notify,
+}
+
+impl DownloadControl {
+    /// Pauses the download after the chunk currently being written
+    pub fn pause(&self) {
+        self.state.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes a paused download, doing nothing if it isn't paused
+    pub fn resume(&self) {
+        self.state.paused.store(false, Ordering::SeqCst);
+        self.state.resumed.notify_waiters();
+    }
+
+    /// Returns true if the download is paused
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the download, also waking it up if it is paused
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.cancel.notify_waiters();
+        self.state.resumed.notify_waiters();
+    }
+
+    /// Returns true if the download was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Waits until the download is cancelled
+    pub(crate) async fn cancelled(&self) {
+        loop {
+            let cancel = self.state.cancel.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            cancel.await;
+        }
+    }
+
+    /// Waits until the download is no longer paused or was cancelled
+    pub(crate) async fn wait_while_paused(&self) {
+        loop {
+            let resumed = self.state.resumed.notified();
+            // Checked after registering, so a resume between the check and the await isn't lost
+            if !self.is_paused() || self.is_cancelled() {
+                return;
+            }
+            resumed.await;
+        }
+    }
+}
@@ -0,0 +1,813 @@
+use futures_util::{ stream, StreamExt };
+use std::{
+    collections::{ hash_map::Entry, HashMap },
+    path::{ Path, PathBuf },
+    process::{ ExitStatus, Stdio },
+    time::{ Duration, Instant },
+};
+use tokio::{ fs::remove_file, io::AsyncReadExt, process::{ Child, Command } };
+
+use crate::{
+    loudnorm::Measurement,
+    DownloadControl,
+    Error,
+    FormatPreference,
+    LoudnessTarget,
+    ProgressEvent,
+    Result,
+    RetryPhase,
+    StreamKind,
+    YouTubeDownloader,
+};
+
+/// FFmpeg messages caused by a truncated or corrupt input rather than by bad arguments
+const INPUT_ERRORS: &[&str] = &[
+    "Invalid data found when processing input",
+    "moov atom not found",
+    "partial file",
+    "Truncating packet",
+    "End of file",
+    "Error while decoding",
+    "could not find codec parameters",
+];
+
+/// Extensions of the files `convert_dir` picks up
+const MEDIA_EXTENSIONS: &[&str] = &["mp4", "webm", "mkv", "mov", "m4a", "weba", "mp3", "opus", "ogg", "wav", "flac"];
+
+/// The audio format downloads are converted to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// Lossless FLAC at the highest compression level
+    #[default]
+    Flac,
+    /// Uncompressed PCM in a WAV container, 16-bit unless `ConversionOptions::pcm_codec` says otherwise
+    Wav,
+    /// MP3 encoded with LAME at the highest VBR quality
+    Mp3,
+    /// Opus in an Ogg container
+    Opus,
+    /// AAC in an M4A container
+    M4a,
+}
+
+impl AudioFormat {
+    /// Returns the file extension of the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Flac => "flac",
+            Self::Wav => "wav",
+            Self::Mp3 => "mp3",
+            Self::Opus => "opus",
+            Self::M4a => "m4a",
+        }
+    }
+
+    /// Returns the prefix of the YouTube codecs the container holds without re-encoding
+    fn copyable_codec(&self) -> Option<&'static str> {
+        match self {
+            Self::Opus => Some("opus"),
+            Self::M4a => Some("mp4a"),
+            Self::Flac | Self::Wav | Self::Mp3 => None,
+        }
+    }
+}
+
+/// Ready-made conversion settings for callers who don't want to pick codecs
+///
+/// Every preset downloads the audio-only stream rather than the full video.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AudioPreset {
+    /// Opus as YouTube serves it, copied into an `.opus` file without re-encoding, or
+    /// re-encoded when the video has no Opus stream
+    Fast,
+    /// Opus re-encoded at 160 kbit/s, small files that keep the quality of the source
+    #[default]
+    Balanced,
+    /// MP3 at LAME's highest VBR quality, playable on practically every device
+    Compatible,
+    /// Lossless FLAC, preserving the decoded source exactly at several times the size
+    Archival,
+}
+
+impl From<AudioPreset> for ConversionOptions {
+    fn from(preset: AudioPreset) -> Self {
+        let (format, copy_audio) = match preset {
+            AudioPreset::Fast => (AudioFormat::Opus, true),
+            AudioPreset::Balanced => (AudioFormat::Opus, false),
+            AudioPreset::Compatible => (AudioFormat::Mp3, false),
+            AudioPreset::Archival => (AudioFormat::Flac, false),
+        };
+        Self { format, copy_audio, convert_source: ConvertSource::AudioOnly, ..Default::default() }
+    }
+}
+
+/// What is downloaded as the intermediate file of a conversion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConvertSource {
+    /// The progressive format carrying video and audio, as before this option existed
+    #[default]
+    FullVideo,
+    /// The best audio-only adaptive stream, saving the bandwidth of the video
+    AudioOnly,
+}
+
+/// Trims silence from the start and end of converted audio with FFmpeg's `silenceremove`
+///
+/// Silence between the first and last sound is kept, so pauses within a track survive.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ ConversionOptions, SilenceTrim };
+/// use std::time::Duration;
+///
+/// let options = ConversionOptions {
+///     trim_silence: Some(SilenceTrim { threshold_db: -60.0, min_duration: Duration::from_millis(200) }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilenceTrim {
+    /// The level in dBFS below which audio counts as silence
+    pub threshold_db: f64,
+    /// How long audio has to stay below the threshold to count as silence
+    pub min_duration: Duration,
+}
+
+impl Default for SilenceTrim {
+    fn default() -> Self {
+        Self { threshold_db: -50.0, min_duration: Duration::from_millis(500) }
+    }
+}
+
+impl SilenceTrim {
+    /// Builds the filter chain, trimming the end by trimming the start of the reversed audio
+    fn filter(&self) -> String {
+        let remove = format!(
+            "silenceremove=start_periods=1:start_duration={}:start_threshold={}dB",
+            self.min_duration.as_secs_f64(),
+            self.threshold_db
+        );
+        format!("{remove},areverse,{remove},areverse")
+    }
+}
+
+/// Options controlling the FFmpeg conversion of downloaded media
+///
+/// # Example
+/// ```
+/// use rustytdown::{ AudioFormat, ConversionOptions };
+///
+/// let options = ConversionOptions {
+///     format: AudioFormat::Wav,
+///     ffmpeg_threads: Some(2),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConversionOptions {
+    /// The audio format to convert to
+    pub format: AudioFormat,
+    /// The PCM codec used for `AudioFormat::Wav` (e.g. "pcm_s24le"), defaulting to "pcm_s16le"
+    pub pcm_codec: Option<String>,
+    /// Caps the number of threads FFmpeg uses (`-threads N`), letting FFmpeg decide when `None`
+    pub ffmpeg_threads: Option<u32>,
+    /// Drops all tags and chapters of the source and omits FFmpeg's encoder tag, producing
+    /// tag-free output
+    pub strip_metadata: bool,
+    /// What is downloaded before converting, defaulting to `ConvertSource::FullVideo`
+    pub convert_source: ConvertSource,
+    /// Normalizes the loudness with a two-pass `loudnorm`, the first pass measuring the input
+    /// and the second applying the correction, leaving the volume untouched when `None`
+    pub loudness: Option<LoudnessTarget>,
+    /// Trims silence from the start and end, before any loudness normalization, keeping the
+    /// audio exactly as downloaded when `None`
+    pub trim_silence: Option<SilenceTrim>,
+    /// Copies the source audio instead of re-encoding it, which only works for
+    /// `AudioFormat::Opus` and `AudioFormat::M4a`
+    ///
+    /// With `ConvertSource::AudioOnly` the audio-only stream is picked from those in a codec
+    /// the container holds (Opus or AAC), falling back to re-encoding when there is none.
+    /// Ignored for the other formats and when `loudness` or `trim_silence` have to re-encode
+    /// anyway.
+    pub copy_audio: bool,
+}
+
+impl ConversionOptions {
+    /// Builds the FFmpeg arguments converting `input` into `output`, normalizing the loudness
+    /// with the first pass `measured`
+    fn ffmpeg_args(&self, input: &str, output: &str, measured: Option<&Measurement>) -> Vec<String> {
+        let mut args = vec!["-i".to_string(), input.to_string(), "-vn".to_string()];
+        if let Some(threads) = self.ffmpeg_threads {
+            args.extend(["-threads".to_string(), threads.to_string()]);
+        }
+        let loudnorm = self.loudness.zip(measured).map(|(target, measured)| target.apply_filter(measured));
+        if let Some(filters) = self.audio_filters(loudnorm) {
+            args.extend(["-af".to_string(), filters]);
+        }
+        if let Some(rate) = measured.and_then(|measured| measured.sample_rate.as_ref()) {
+            args.extend(["-ar".to_string(), rate.clone()]);
+        }
+        args.extend(self.codec_args());
+        if self.strip_metadata {
+            // bitexact keeps FFmpeg from writing its own encoder tag
+            args.extend(
+                ["-map_metadata", "-1", "-map_chapters", "-1", "-fflags", "+bitexact", "-flags:a", "+bitexact"]
+                    .into_iter()
+                    .map(String::from)
+            );
+        }
+        args.extend(["-y".to_string(), output.to_string()]);
+        args
+    }
+
+    /// Builds the FFmpeg arguments selecting the audio encoder of `format`
+    fn codec_args(&self) -> Vec<String> {
+        let codec_args: Vec<&str> = match self.format {
+            _ if self.copies_audio() => vec!["-acodec", "copy"],
+            AudioFormat::Flac => vec!["-acodec", "flac", "-compression_level", "8"],
+            AudioFormat::Wav => vec!["-acodec", self.pcm_codec.as_deref().unwrap_or("pcm_s16le")],
+            AudioFormat::Mp3 => vec!["-acodec", "libmp3lame", "-q:a", "0"],
+            AudioFormat::Opus => vec!["-acodec", "libopus", "-b:a", "160k"],
+            AudioFormat::M4a => vec!["-acodec", "aac", "-b:a", "192k"],
+        };
+        codec_args.into_iter().map(String::from).collect()
+    }
+
+    /// Returns true if the audio stream is copied rather than re-encoded
+    fn copies_audio(&self) -> bool {
+        self.copy_audio &&
+            self.format.copyable_codec().is_some() &&
+            self.loudness.is_none() &&
+            self.trim_silence.is_none()
+    }
+
+    /// Chains the silence trim with a `loudnorm` filter, `None` if neither applies
+    fn audio_filters(&self, loudnorm: Option<String>) -> Option<String> {
+        let filters: Vec<String> = self.trim_silence
+            .map(|trim| trim.filter())
+            .into_iter()
+            .chain(loudnorm)
+            .collect();
+        (!filters.is_empty()).then(|| filters.join(","))
+    }
+
+    /// Builds the FFmpeg arguments of the measuring `loudnorm` pass, which writes no output
+    fn measure_args(&self, input: &str, target: &LoudnessTarget) -> Vec<String> {
+        let mut args = vec!["-i".to_string(), input.to_string(), "-vn".to_string()];
+        if let Some(threads) = self.ffmpeg_threads {
+            args.extend(["-threads".to_string(), threads.to_string()]);
+        }
+        let filters = self.audio_filters(Some(target.measure_filter())).unwrap_or_default();
+        args.extend(["-af".to_string(), filters, "-f".to_string(), "null".to_string()]);
+        args.push("-".to_string());
+        args
+    }
+}
+
+impl YouTubeDownloader {
+    /// Downloads a YouTube video and converts it to audio with a preset
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `preset` - The codec settings to use, see `AudioPreset` for what each one maps to
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ AudioPreset, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let audio_path = downloader.download_and_convert_preset("dQw4w9WgXcQ", AudioPreset::Compatible).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_and_convert_preset(&self, video_id: &str, preset: AudioPreset) -> Result<String> {
+        self.download_and_convert_with(video_id, &preset.into()).await
+    }
+
+    /// Downloads a YouTube video and converts it to audio with custom conversion options
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `options` - The options for the FFmpeg conversion
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ ConversionOptions, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let options = ConversionOptions { ffmpeg_threads: Some(2), ..Default::default() };
+    /// let audio_path = downloader.download_and_convert_with("dQw4w9WgXcQ", &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_and_convert_with(
+        &self,
+        video_id: &str,
+        options: &ConversionOptions
+    ) -> Result<String> {
+        self.check_archive(video_id).await?;
+        let (url, options) = self.convert_source_url(video_id, options).await?;
+        let output = format!("{video_id}.{}", options.format.extension());
+        let path = self.convert_url(&url, Path::new(&output), &options, None).await?;
+        self.record_archive(video_id).await?;
+        Ok(path)
+    }
+
+    /// Downloads and converts a YouTube video like `download_and_convert_with`, with a handle
+    /// to pause, resume and cancel it
+    ///
+    /// Cancelling works in both phases: during the download the partial file is removed,
+    /// during the conversion FFmpeg is killed and both the download and the unfinished
+    /// output are removed. Either way the future fails with `Error::Cancelled`.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `options` - The options for the FFmpeg conversion
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ ConversionOptions, Error, YouTubeDownloader };
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let options = ConversionOptions::default();
+    /// let (control, download) = downloader.download_and_convert_controlled("dQw4w9WgXcQ", &options);
+    /// let canceller = async {
+    ///     tokio::time::sleep(Duration::from_secs(5)).await;
+    ///     control.cancel();
+    /// };
+    /// let (result, _) = tokio::join!(download, canceller);
+    /// assert!(matches!(result, Err(Error::Cancelled) | Ok(_)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download_and_convert_controlled(
+        &self,
+        video_id: &str,
+        options: &ConversionOptions
+    ) -> (DownloadControl, impl std::future::Future<Output = Result<String>> + '_) {
+        let control = DownloadControl::default();
+        let handle = control.clone();
+        let (video_id, options) = (video_id.to_string(), options.clone());
+
+        let download = async move {
+            self.check_archive(&video_id).await?;
+            let (url, options) = self.convert_source_url(&video_id, &options).await?;
+            let output = format!("{video_id}.{}", options.format.extension());
+            let path = self.convert_url(&url, Path::new(&output), &options, Some(&handle)).await?;
+            self.record_archive(&video_id).await?;
+            Ok(path)
+        };
+        (control, download)
+    }
+
+    /// Resolves the URL of the intermediate download of a conversion, with the options to
+    /// convert it with
+    ///
+    /// Copying audio needs an audio-only stream in a codec the container holds; when the
+    /// video has none, the best audio-only stream is re-encoded instead.
+    pub(crate) async fn convert_source_url(
+        &self,
+        video_id: &str,
+        options: &ConversionOptions
+    ) -> Result<(String, ConversionOptions)> {
+        let mut options = options.clone();
+        let url = match options.convert_source {
+            ConvertSource::FullVideo => self.get_video_url(video_id).await?,
+            ConvertSource::AudioOnly => {
+                let formats = self.list_formats(video_id).await?;
+                let audio = FormatPreference::new(StreamKind::AudioOnly);
+                // A copied stream has to be in a codec the output container holds
+                let copyable = options.format.copyable_codec().filter(|_| options.copies_audio());
+                let copied = copyable.and_then(|codec| {
+                    let formats: Vec<_> = formats
+                        .iter()
+                        .filter(|format| format.codecs().iter().any(|c| c.starts_with(codec)))
+                        .cloned()
+                        .collect();
+                    audio.select(&formats).cloned()
+                });
+                if copyable.is_some() && copied.is_none() {
+                    options.copy_audio = false;
+                }
+                copied
+                    .or_else(|| audio.select(&formats).cloned())
+                    .and_then(|format| format.url)
+                    .ok_or_else(|| Error::Api("No audio-only stream found".into()))?
+            }
+        };
+        Ok((url, options))
+    }
+
+    /// Downloads a direct media URL next to `output` and converts it into `output`
+    pub(crate) async fn convert_url(
+        &self,
+        url: &str,
+        output: &Path,
+        options: &ConversionOptions,
+        control: Option<&DownloadControl>
+    ) -> Result<String> {
+        let start_time = Instant::now();
+        let audio_path = output.to_string_lossy().into_owned();
+        // FFmpeg can't write into its own input, so an ".mp4" output gets a distinct intermediate
+        let video_path = match output.with_extension("mp4") {
+            path if path == output => output.with_extension("source.mp4"),
+            path => path,
+        };
+        let video_path = video_path.to_string_lossy().into_owned();
+
+        let mut retry = 0;
+        let ttfb = loop {
+            let pb = Self::progress_bar()?;
+            let ttfb = self.download_url_controlled(url, &video_path, &pb, control, None).await?;
+
+            pb.finish_with_message("Converting to audio...");
+
+            self.report(ProgressEvent::ConvertingStarted);
+            match Self::convert(&video_path, &audio_path, options, control).await {
+                Ok(()) => {
+                    self.report(ProgressEvent::ConvertingFinished);
+                    break ttfb;
+                }
+                Err(Error::Cancelled) => {
+                    remove_file(&video_path).await?;
+                    return Err(Error::Cancelled);
+                }
+                // A corrupt download is worth fetching again, bad arguments would fail again
+                Err(Error::CorruptInput(e)) if retry < self.retry_policy.retries_for(RetryPhase::Conversion) => {
+                    retry += 1;
+                    let delay = self.retrying(RetryPhase::Conversion, retry, e);
+                    remove_file(&video_path).await?;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        };
+
+        remove_file(&video_path).await?;
+
+        let total_duration = start_time.elapsed();
+        println!(
+            "Download and conversion complete! TTFB: {:.2?}, Total time: {:.2?}",
+            ttfb,
+            total_duration
+        );
+
+        self.finalize_output(&audio_path).await?;
+        Ok(audio_path)
+    }
+
+    /// Converts a local media file to audio, writing it next to the input
+    ///
+    /// The output has the input's name with the extension of `options.format`. No network
+    /// request is made, so this works for files that didn't come from this crate too.
+    ///
+    /// # Arguments
+    /// * `input` - The media file to convert
+    /// * `options` - The options for the FFmpeg conversion
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ ConversionOptions, YouTubeDownloader };
+    /// use std::path::Path;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let audio_path = downloader.convert_file(Path::new("video.mp4"), &ConversionOptions::default()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn convert_file(&self, input: &Path, options: &ConversionOptions) -> Result<String> {
+        let input_path = input.to_string_lossy().into_owned();
+        let output_path = input.with_extension(options.format.extension()).to_string_lossy().into_owned();
+        if input_path == output_path {
+            return Err(Error::Conversion(format!("{input_path} already has the target format")));
+        }
+
+        Self::convert(&input_path, &output_path, options, None).await?;
+
+        self.finalize_output(&output_path).await?;
+        Ok(output_path)
+    }
+
+    /// Re-muxes a local media file into the container of `output` without re-encoding
+    ///
+    /// Every stream is copied as is, so this is fast and lossless but fails when the target
+    /// container can't hold one of the codecs (e.g. VP9 video in an AVI).
+    ///
+    /// # Arguments
+    /// * `input` - The media file to re-mux
+    /// * `output` - The file to write, its extension picks the container (e.g. "video.mkv")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let video_path = downloader.download_video("dQw4w9WgXcQ").await?;
+    /// let mkv_path = downloader.remux(&video_path, "rickroll.mkv").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn remux(&self, input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<String> {
+        let input_path = input.as_ref().to_string_lossy().into_owned();
+        let output_path = output.as_ref().to_string_lossy().into_owned();
+        if input_path == output_path {
+            return Err(Error::Conversion(format!("{input_path} can't be re-muxed into itself")));
+        }
+
+        let args = ["-i", &input_path, "-map", "0", "-c", "copy", "-y", &output_path];
+        Self::run_ffmpeg(args.into_iter().map(String::from).collect(), Some(&output_path), None).await?;
+
+        self.finalize_output(&output_path).await?;
+        Ok(output_path)
+    }
+
+    /// Downloads the audio of a YouTube video once and splits it into parts of equal length
+    ///
+    /// FFmpeg's segment muxer writes `<video_id>.000.<ext>`, `<video_id>.001.<ext>` and so on
+    /// into `out_dir`, each `segment` long except the last one, which holds whatever remains.
+    /// A video shorter than `segment` yields a single file. Useful for transcription services
+    /// and uploads with a length or size limit.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `format` - The audio format of the segments
+    /// * `segment` - The length of every segment but the last
+    /// * `out_dir` - The directory the segments are written to
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ AudioFormat, YouTubeDownloader };
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let parts = downloader
+    ///     .download_audio_segments("dQw4w9WgXcQ", AudioFormat::Mp3, Duration::from_secs(60), "parts").await?;
+    /// println!("Split into {} parts", parts.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_audio_segments(
+        &self,
+        video_id: &str,
+        format: AudioFormat,
+        segment: Duration,
+        out_dir: impl AsRef<Path>
+    ) -> Result<Vec<String>> {
+        if segment.is_zero() {
+            return Err(Error::Conversion("Segments must be longer than zero".into()));
+        }
+        let options = ConversionOptions { format, convert_source: ConvertSource::AudioOnly, ..Default::default() };
+        let out_dir = out_dir.as_ref();
+        tokio::fs::create_dir_all(out_dir).await?;
+
+        let (url, _) = self.convert_source_url(video_id, &options).await?;
+        let source_path = out_dir.join(format!("{video_id}.mp4")).to_string_lossy().into_owned();
+        let pb = Self::progress_bar()?;
+        self.download_url(&url, &source_path, &pb).await?;
+        pb.finish_with_message("Splitting audio...");
+
+        let list_path = out_dir.join(format!("{video_id}.segments.txt"));
+        let pattern = out_dir.join(format!("{video_id}.%03d.{}", format.extension()));
+        let mut args = vec!["-i".to_string(), source_path.clone(), "-map".to_string(), "0:a".to_string()];
+        args.extend(options.codec_args());
+        args.extend(
+            [
+                "-f".to_string(),
+                "segment".to_string(),
+                "-segment_time".to_string(),
+                format!("{:.3}", segment.as_secs_f64()),
+                "-reset_timestamps".to_string(),
+                "1".to_string(),
+                "-segment_list".to_string(),
+                list_path.to_string_lossy().into_owned(),
+                "-segment_list_type".to_string(),
+                "flat".to_string(),
+                "-y".to_string(),
+                pattern.to_string_lossy().into_owned(),
+            ]
+        );
+
+        self.report(ProgressEvent::ConvertingStarted);
+        let result = Self::run_ffmpeg(args, None, None).await;
+        remove_file(&source_path).await?;
+        // The list names every segment written, even if FFmpeg failed partway
+        let list = tokio::fs::read_to_string(&list_path).await.unwrap_or_default();
+        let _ = remove_file(&list_path).await;
+        let segments: Vec<String> = list
+            .lines()
+            .filter(|name| !name.is_empty())
+            .map(|name| out_dir.join(name).to_string_lossy().into_owned())
+            .collect();
+        if let Err(e) = result {
+            for segment in &segments {
+                let _ = remove_file(segment).await;
+            }
+            return Err(e);
+        }
+        self.report(ProgressEvent::ConvertingFinished);
+
+        for segment in &segments {
+            self.finalize_output(segment).await?;
+        }
+        Ok(segments)
+    }
+
+    /// Runs `ffmpeg -version`, returning the version line FFmpeg prints first
+    ///
+    /// Fails with `Error::FfmpegUnavailable` if FFmpeg isn't installed or can't be run.
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// println!("{}", downloader.check_ffmpeg().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn check_ffmpeg(&self) -> Result<String> {
+        let output = Command::new("ffmpeg")
+            .arg("-version")
+            .stdin(Stdio::null())
+            .output().await
+            .map_err(|e| Error::FfmpegUnavailable(e.to_string()))?;
+        if !output.status.success() {
+            return Err(Error::FfmpegUnavailable(format!("ffmpeg -version exited with {}", output.status)));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().next().unwrap_or_default().to_string())
+    }
+
+    /// Converts every media file directly inside `input_dir` to `format`
+    ///
+    /// Files with a video or audio extension (e.g. mp4, webm, mkv, m4a) are picked up and
+    /// converted next to themselves, with at most `concurrency` FFmpeg processes at once.
+    /// Files already in `format`, such as the outputs of an earlier run, are skipped. The
+    /// results are returned in directory order, one per converted file, so a failing file
+    /// doesn't fail the others. Of several files sharing a name (e.g. "a.mp4" and "a.webm")
+    /// only the first is converted, the others fail rather than overwrite its output.
+    ///
+    /// # Arguments
+    /// * `input_dir` - The directory to scan, subdirectories are not entered
+    /// * `format` - The audio format to convert to
+    /// * `concurrency` - The maximum number of simultaneous conversions
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ AudioFormat, YouTubeDownloader };
+    /// use std::path::Path;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for result in downloader.convert_dir(Path::new("videos"), AudioFormat::Flac, 4).await {
+    ///     match result {
+    ///         Ok(path) => println!("Converted {path}"),
+    ///         Err(e) => eprintln!("{e}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn convert_dir(&self, input_dir: &Path, format: AudioFormat, concurrency: usize) -> Vec<Result<String>> {
+        let inputs = match media_files(input_dir, format.extension()).await {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                return vec![Err(e.into())];
+            }
+        };
+
+        // Claim each output path up front, as colliding conversions would run concurrently
+        let mut outputs: HashMap<PathBuf, PathBuf> = HashMap::new();
+        let inputs: Vec<Result<PathBuf>> = inputs
+            .into_iter()
+            .map(|input| match outputs.entry(input.with_extension(format.extension())) {
+                Entry::Occupied(first) => {
+                    Err(
+                        Error::Conversion(
+                            format!(
+                                "{} would overwrite {}, which is converted from {}",
+                                input.display(),
+                                first.key().display(),
+                                first.get().display()
+                            )
+                        )
+                    )
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(input.clone());
+                    Ok(input)
+                }
+            })
+            .collect();
+
+        let options = ConversionOptions { format, ..Default::default() };
+        stream::iter(inputs)
+            .map(|input| {
+                let options = &options;
+                async move { self.convert_file(&input?, options).await }
+            })
+            .buffered(concurrency.max(1))
+            .collect().await
+    }
+
+    /// Converts a downloaded media file to audio with FFmpeg
+    ///
+    /// Failures caused by the input file are reported as `Error::CorruptInput` so callers can
+    /// tell them apart from failures that re-downloading won't fix. Cancelling `control` kills
+    /// FFmpeg and removes the unfinished output.
+    async fn convert(
+        input: &str,
+        output: &str,
+        options: &ConversionOptions,
+        control: Option<&DownloadControl>
+    ) -> Result<()> {
+        let measured = match &options.loudness {
+            Some(target) => {
+                let stderr = Self::run_ffmpeg(options.measure_args(input, target), None, control).await?;
+                Some(Measurement::parse(&stderr)?)
+            }
+            None => None,
+        };
+        Self::run_ffmpeg(options.ffmpeg_args(input, output, measured.as_ref()), Some(output), control).await?;
+        Ok(())
+    }
+
+    /// Runs FFmpeg to completion, returning its stderr
+    ///
+    /// Cancelling `control` kills FFmpeg and removes the unfinished `output`.
+    pub(crate) async fn run_ffmpeg(
+        args: Vec<String>,
+        output: Option<&str>,
+        control: Option<&DownloadControl>
+    ) -> Result<String> {
+        let mut child = Command::new("ffmpeg")
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let finished = match control {
+            Some(control) => {
+                tokio::select! {
+                    result = wait_with_stderr(&mut child) => Some(result),
+                    () = control.cancelled() => None,
+                }
+            }
+            None => Some(wait_with_stderr(&mut child).await),
+        };
+        let Some(result) = finished else {
+            child.kill().await?;
+            if let Some(output) = output {
+                let _ = remove_file(output).await;
+            }
+            return Err(Error::Cancelled);
+        };
+
+        let (status, stderr) = result?;
+        if !status.success() {
+            let message = stderr.lines().last().unwrap_or("Failed to convert video to audio").to_string();
+            if INPUT_ERRORS.iter().any(|marker| stderr.contains(marker)) {
+                return Err(Error::CorruptInput(message));
+            }
+            return Err(Error::Conversion(message));
+        }
+        Ok(stderr)
+    }
+}
+
+/// Waits for FFmpeg to exit, collecting what it wrote to stderr
+async fn wait_with_stderr(child: &mut Child) -> std::io::Result<(ExitStatus, String)> {
+    let mut stderr = Vec::new();
+    if let Some(pipe) = child.stderr.as_mut() {
+        pipe.read_to_end(&mut stderr).await?;
+    }
+    Ok((child.wait().await?, String::from_utf8_lossy(&stderr).into_owned()))
+}
+
+/// Lists the media files directly inside `dir` that aren't `target` files already, sorted by path
+async fn media_files(dir: &Path, target: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let extension = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase);
+        let media = extension.is_some_and(|e| MEDIA_EXTENSIONS.contains(&e.as_str()) && e != target);
+        if path.is_file() && media {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
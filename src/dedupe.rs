@@ -0,0 +1,145 @@
+use serde_json::{ Map, Value };
+use sha2::{ Digest, Sha256 };
+use std::{ fmt, path::{ Path, PathBuf } };
+use tokio::{ fs::{ self, File }, io::AsyncReadExt, sync::Mutex };
+
+use crate::{ Error, Result };
+
+/// Computes the hex encoded SHA-256 of a file without loading it into memory
+pub(crate) async fn hash_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    )
+}
+
+/// Moves a downloaded file into a content-addressed store and links it back to `path`
+///
+/// The store keeps one file per SHA-256 hash. If the content is already stored the fresh
+/// download is dropped and `path` becomes a link to the existing copy. Hard links are used
+/// where possible, falling back to symlinks when the store lives on another filesystem.
+pub(crate) async fn dedupe_into_store(store: &Path, path: &Path, hash: &str) -> Result<()> {
+    let stored = match path.extension() {
+        Some(ext) => store.join(format!("{hash}.{}", ext.to_string_lossy())),
+        None => store.join(hash),
+    };
+
+    if fs::try_exists(&stored).await? {
+        fs::remove_file(path).await?;
+    } else {
+        fs::create_dir_all(store).await?;
+        if fs::hard_link(path, &stored).await.is_ok() {
+            return Ok(());
+        }
+        if fs::rename(path, &stored).await.is_err() {
+            fs::copy(path, &stored).await?;
+            fs::remove_file(path).await?;
+        }
+    }
+
+    link(&stored, path).await
+}
+
+/// Replaces `path` by a hard link to `original`, or a symlink if they are on different filesystems
+async fn link(original: &Path, path: &Path) -> Result<()> {
+    if fs::hard_link(original, path).await.is_err() {
+        symlink(&fs::canonicalize(original).await?, path)?;
+    }
+    Ok(())
+}
+
+/// A JSON file mapping content hashes to the first downloaded file with that content
+///
+/// The index is read on first use and rewritten through a temporary file on every new
+/// entry, so an interrupted write never leaves a truncated index behind. The lock
+/// serializes the lookup-and-insert of concurrent downloads, so two downloads of the same
+/// content can't both be indexed.
+pub(crate) struct DedupIndex {
+    path: PathBuf,
+    entries: Mutex<Option<Map<String, Value>>>,
+}
+
+impl DedupIndex {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path, entries: Mutex::new(None) }
+    }
+
+    async fn load(&self) -> Result<Map<String, Value>> {
+        match fs::read(&self.path).await {
+            Ok(contents) =>
+                serde_json::from_slice(&contents).map_err(|e| {
+                    Error::Api(format!("Invalid dedup index {}: {e}", self.path.display()))
+                }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn save(&self, entries: &Map<String, Value>) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_vec_pretty(entries).map_err(|e| Error::Api(e.to_string()))?;
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, json).await?;
+        fs::rename(&temp, &self.path).await?;
+        Ok(())
+    }
+
+    /// Links `path` to the indexed file with the same `hash`, or indexes `path` if there is none
+    pub(crate) async fn dedupe(&self, path: &Path, hash: &str) -> Result<()> {
+        let path = std::path::absolute(path)?;
+        let mut entries = self.entries.lock().await;
+        if entries.is_none() {
+            *entries = Some(self.load().await?);
+        }
+        let Some(entries) = entries.as_mut() else {
+            return Ok(());
+        };
+
+        let indexed = entries.get(hash).and_then(Value::as_str).map(PathBuf::from);
+        if let Some(indexed) = indexed {
+            if indexed == path {
+                return Ok(());
+            }
+            if fs::try_exists(&indexed).await? {
+                fs::remove_file(&path).await?;
+                return link(&indexed, &path).await;
+            }
+        }
+
+        entries.insert(hash.to_string(), Value::String(path.to_string_lossy().into_owned()));
+        self.save(entries).await
+    }
+}
+
+impl fmt::Debug for DedupIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DedupIndex").field("path", &self.path).finish()
+    }
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
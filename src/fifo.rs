@@ -0,0 +1,40 @@
+use std::path::Path;
+use tokio::fs::{ File, OpenOptions, remove_file };
+
+use crate::Result;
+
+/// Returns true if `path` is a named pipe, which downloads stream into without seeking
+///
+/// A FIFO can't be truncated, preallocated, resumed or renamed over, and deleting it on a
+/// failed download would break the reader's setup, so the download paths check for one
+/// before touching the output.
+pub(crate) async fn is_fifo(path: impl AsRef<Path>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        tokio::fs::metadata(path).await.is_ok_and(|metadata| metadata.file_type().is_fifo())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+/// Opens the output of a fresh download, writing into a FIFO as is and truncating any other file
+///
+/// Opening a FIFO for writing blocks until a reader opens the other end.
+pub(crate) async fn create_output(path: &str) -> Result<File> {
+    match is_fifo(path).await {
+        true => Ok(OpenOptions::new().write(true).open(path).await?),
+        false => Ok(File::create(path).await?),
+    }
+}
+
+/// Removes the output of a cancelled or failed download, leaving a FIFO in place
+pub(crate) async fn discard_output(path: &str) -> Result<()> {
+    match is_fifo(path).await {
+        true => Ok(()),
+        false => Ok(remove_file(path).await?),
+    }
+}
@@ -0,0 +1,391 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::Error;
+
+/// A single stream format offered by the player response
+#[derive(Debug, Clone)]
+pub struct FormatInfo {
+    /// The YouTube format identifier (e.g. 18 for 360p MP4)
+    pub itag: u32,
+    /// The direct stream URL, if YouTube provided one without a cipher
+    pub url: Option<String>,
+    /// The full mime type including codecs (e.g. `video/mp4; codecs="avc1.42001E, mp4a.40.2"`)
+    pub mime_type: String,
+    /// The peak bitrate in bits per second
+    pub bitrate: Option<u64>,
+    /// The average bitrate in bits per second
+    pub average_bitrate: Option<u64>,
+    /// The width in pixels for formats carrying video
+    pub width: Option<u32>,
+    /// The height in pixels for formats carrying video
+    pub height: Option<u32>,
+    /// The frame rate for formats carrying video
+    pub fps: Option<u32>,
+    /// The human readable quality label (e.g. "1080p60")
+    pub quality_label: Option<String>,
+    /// The size of the stream in bytes, if known
+    pub content_length: Option<u64>,
+    /// The duration of the stream in milliseconds, if known
+    pub approx_duration_ms: Option<u64>,
+    /// Whether the format came from `adaptiveFormats` (a single video or audio stream)
+    pub adaptive: bool,
+    /// The inclusive byte range of the container's initialization data, for adaptive formats
+    pub init_range: Option<(u64, u64)>,
+}
+
+impl FormatInfo {
+    pub(crate) fn from_json(format: &Value, adaptive: bool) -> Option<Self> {
+        Some(Self {
+            itag: format["itag"].as_u64()? as u32,
+            url: format["url"].as_str().map(String::from),
+            mime_type: format["mimeType"].as_str().unwrap_or_default().to_string(),
+            bitrate: format["bitrate"].as_u64(),
+            average_bitrate: format["averageBitrate"].as_u64(),
+            width: format["width"].as_u64().map(|w| w as u32),
+            height: format["height"].as_u64().map(|h| h as u32),
+            fps: format["fps"].as_u64().map(|f| f as u32),
+            quality_label: format["qualityLabel"].as_str().map(String::from),
+            content_length: format["contentLength"].as_str().and_then(|l| l.parse().ok()),
+            approx_duration_ms: format["approxDurationMs"].as_str().and_then(|d| d.parse().ok()),
+            adaptive,
+            init_range: parse_range(&format["initRange"]),
+        })
+    }
+
+    /// Returns true if the format carries a video stream
+    pub fn has_video(&self) -> bool {
+        self.mime_type.starts_with("video/")
+    }
+
+    /// Returns true if the format carries an audio stream
+    pub fn has_audio(&self) -> bool {
+        self.mime_type.starts_with("audio/") || self.is_muxed()
+    }
+
+    /// Returns the codecs listed in the mime type (e.g. `["avc1.42001E", "mp4a.40.2"]`)
+    pub fn codecs(&self) -> Vec<&str> {
+        self.mime_type
+            .split_once("codecs=")
+            .map(|(_, codecs)| {
+                codecs
+                    .trim_matches(|c| c == '"' || c == ' ')
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|codec| !codec.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns the codec names of the format, normalized as accepted by
+    /// `FormatPreference::codec_filter` (e.g. `["h264", "aac"]`)
+    pub fn codec_names(&self) -> Vec<String> {
+        self.codecs().into_iter().map(codec_name).collect()
+    }
+
+    /// Returns true if the format really carries both a video and an audio stream
+    ///
+    /// YouTube occasionally lists audio-less entries among the progressive `formats`, so the
+    /// codec list is checked for both a video and an audio codec. Formats without a codec list
+    /// fall back to trusting the progressive listing.
+    pub fn is_muxed(&self) -> bool {
+        if self.adaptive || !self.has_video() {
+            return false;
+        }
+        let codecs = self.codecs();
+        if codecs.is_empty() {
+            return true;
+        }
+        codecs.iter().any(|codec| is_video_codec(codec)) && codecs.iter().any(|codec| !is_video_codec(codec))
+    }
+
+    /// Returns the size of the stream in bytes, estimated from the bitrate when YouTube
+    /// doesn't report a `contentLength`
+    pub fn estimated_size(&self) -> Option<u64> {
+        self.content_length.or_else(|| {
+            let bitrate = self.average_bitrate.or(self.bitrate)?;
+            Some((bitrate * self.approx_duration_ms?) / 8 / 1000)
+        })
+    }
+
+    /// Returns the file extension matching the format's container
+    pub fn extension(&self) -> &'static str {
+        match (self.has_video(), self.mime_type.contains("/webm")) {
+            (true, true) => "webm",
+            (true, false) => "mp4",
+            (false, true) => "weba",
+            (false, false) => "m4a",
+        }
+    }
+}
+
+/// Returns true for codec strings of video codecs (e.g. "avc1.42001E" or "vp09.00.40.08")
+fn is_video_codec(codec: &str) -> bool {
+    ["avc1", "avc3", "hev1", "hvc1", "vp8", "vp9", "vp09", "av01", "mp4v"]
+        .iter()
+        .any(|prefix| codec.starts_with(prefix))
+}
+
+/// Parses a `{ "start": "0", "end": "740" }` byte range
+fn parse_range(range: &Value) -> Option<(u64, u64)> {
+    let bound = |key: &str| range[key].as_str().and_then(|b| b.parse().ok());
+    Some((bound("start")?, bound("end")?))
+}
+
+/// Parses both `formats` and `adaptiveFormats` out of a player response
+pub(crate) fn parse_formats(player_response: &Value) -> Vec<FormatInfo> {
+    let streaming_data = &player_response["streamingData"];
+    let progressive = streaming_data["formats"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|format| FormatInfo::from_json(format, false));
+    let adaptive = streaming_data["adaptiveFormats"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|format| FormatInfo::from_json(format, true));
+    progressive.chain(adaptive).collect()
+}
+
+/// The kind of stream a format selection is made from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamKind {
+    /// Progressive formats carrying both video and audio
+    #[default]
+    Muxed,
+    /// Adaptive formats carrying only video
+    VideoOnly,
+    /// Adaptive formats carrying only audio
+    AudioOnly,
+}
+
+impl StreamKind {
+    fn matches(&self, format: &FormatInfo) -> bool {
+        match self {
+            Self::Muxed => format.is_muxed(),
+            Self::VideoOnly => format.adaptive && format.has_video(),
+            Self::AudioOnly => format.adaptive && !format.has_video(),
+        }
+    }
+}
+
+/// Maps a codec of a mime type (e.g. "avc1.42001E") or a common codec name (e.g. "AVC") to
+/// the name used by `FormatPreference::codec_filter`
+fn codec_name(codec: &str) -> String {
+    let codec = codec.trim().to_lowercase();
+    let family = codec.split('.').next().unwrap_or_default();
+    let name = match family {
+        "avc1" | "avc3" | "avc" | "h264" => "h264",
+        "hev1" | "hvc1" | "hevc" | "h265" => "h265",
+        "vp09" | "vp9" => "vp9",
+        "vp8" => "vp8",
+        "av01" | "av1" => "av1",
+        "mp4a" | "aac" => "aac",
+        "ac-3" | "ac3" => "ac3",
+        "ec-3" | "eac3" => "eac3",
+        other => other,
+    };
+    name.to_string()
+}
+
+/// Returns the container extension able to hold a video-only and an audio-only format
+/// without re-encoding
+pub(crate) fn merged_extension(video: &FormatInfo, audio: &FormatInfo) -> &'static str {
+    match (video.extension(), audio.extension()) {
+        ("mp4", "m4a") => "mp4",
+        ("webm", "weba") => "webm",
+        _ => "mkv",
+    }
+}
+
+/// How the remaining formats are ranked once the constraints have been applied
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// Picks the highest resolution, then the highest frame rate and bitrate
+    #[default]
+    Best,
+    /// Picks the highest resolution, then the smallest file regardless of codec or container
+    ///
+    /// VP9/WebM is often smaller than H.264/MP4 at the same resolution, so this favours
+    /// storage over compatibility. The chosen size is available from
+    /// `FormatInfo::estimated_size`.
+    PreferSmallest,
+}
+
+/// Constraints used to pick the best format out of a format list
+///
+/// Formats of the requested `StreamKind` that violate a constraint are discarded, and the
+/// highest quality remaining format is picked.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ FormatPreference, StreamKind };
+///
+/// let preference = FormatPreference::new(StreamKind::VideoOnly)
+///     .max_height(1080)
+///     .max_bitrate(2500)
+///     .fps_preference(30);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FormatPreference {
+    kind: StreamKind,
+    mode: SelectionMode,
+    max_height: Option<u32>,
+    max_bitrate: Option<u64>,
+    fps: Option<u32>,
+    codecs: Vec<String>,
+}
+
+impl FormatPreference {
+    /// Creates a preference selecting from the given kind of stream
+    pub fn new(kind: StreamKind) -> Self {
+        Self { kind, ..Self::default() }
+    }
+
+    /// Sets how formats satisfying the constraints are ranked
+    pub fn mode(mut self, mode: SelectionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Only considers formats whose height is at most `height` pixels (e.g. 1080)
+    pub fn max_height(mut self, height: u32) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+
+    /// Only considers formats whose bitrate is at most `kbps` kilobits per second
+    ///
+    /// The average bitrate is compared when YouTube reports it, otherwise the peak bitrate.
+    pub fn max_bitrate(mut self, kbps: u64) -> Self {
+        self.max_bitrate = Some(kbps);
+        self
+    }
+
+    /// Prefers formats with the given frame rate among those of the best resolution
+    ///
+    /// This is a preference, not a constraint: if the rate isn't offered at that resolution the
+    /// closest available rate is picked. The chosen rate is reported in `FormatInfo::fps`.
+    pub fn fps_preference(mut self, fps: u32) -> Self {
+        self.fps = Some(fps);
+        self
+    }
+
+    /// Only considers formats using one of the given codecs, e.g. for hardware decoding
+    ///
+    /// Accepts "h264", "h265", "vp9", "av1", "aac", "opus" and "vorbis", as well as the
+    /// names used in mime types such as "avc1" or "av01". A format matches if any of its
+    /// codecs is listed, so a muxed H.264/AAC format matches both "h264" and "aac". If no
+    /// format of the stream kind uses a listed codec, `select_format` and `download` fail with
+    /// `Error::CodecUnavailable` naming the codecs on offer.
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ DownloadRequest, FormatPreference, MediaKind, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let request = DownloadRequest::new("dQw4w9WgXcQ")
+    ///     .kind(MediaKind::Best)
+    ///     .preference(FormatPreference::default().codec_filter(["av1"]));
+    /// downloader.download(request).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn codec_filter<I, S>(mut self, codecs: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        self.codecs = codecs
+            .into_iter()
+            .map(|codec| codec_name(codec.as_ref()))
+            .collect();
+        self
+    }
+
+    /// Returns `Error::CodecUnavailable` if no format of the stream kind uses a filtered codec
+    pub(crate) fn codec_error(&self, formats: &[FormatInfo]) -> Option<Error> {
+        if self.codecs.is_empty() {
+            return None;
+        }
+        let mut available: Vec<String> = formats
+            .iter()
+            .filter(|format| format.url.is_some() && self.kind.matches(format))
+            .flat_map(FormatInfo::codec_names)
+            .collect();
+        if available.iter().any(|codec| self.codecs.contains(codec)) {
+            return None;
+        }
+        available.sort();
+        available.dedup();
+        Some(Error::CodecUnavailable { requested: self.codecs.clone(), available })
+    }
+
+    pub(crate) fn with_kind(mut self, kind: StreamKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    fn allows(&self, format: &FormatInfo) -> bool {
+        if !self.codecs.is_empty() && !format.codec_names().iter().any(|codec| self.codecs.contains(codec)) {
+            return false;
+        }
+        if let (Some(max), Some(height)) = (self.max_height, format.height) {
+            if height > max {
+                return false;
+            }
+        }
+        if let Some(kbps) = self.max_bitrate {
+            match format.average_bitrate.or(format.bitrate) {
+                Some(bitrate) if bitrate <= kbps * 1000 => {}
+                _ => {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Picks the best downloadable format satisfying this preference
+    pub fn select<'a>(&self, formats: &'a [FormatInfo]) -> Option<&'a FormatInfo> {
+        formats
+            .iter()
+            .filter(|format| format.url.is_some() && self.kind.matches(format) && self.allows(format))
+            .max_by(|a, b| self.compare(a, b))
+    }
+
+    /// Returns every downloadable format satisfying this preference, best first
+    pub(crate) fn ranked<'a>(&self, formats: &'a [FormatInfo]) -> Vec<&'a FormatInfo> {
+        let mut ranked: Vec<_> = formats
+            .iter()
+            .filter(|format| format.url.is_some() && self.kind.matches(format) && self.allows(format))
+            .collect();
+        ranked.sort_by(|a, b| self.compare(b, a));
+        ranked
+    }
+
+    /// Orders formats by quality, so that the greater format is the preferred one
+    fn compare(&self, a: &FormatInfo, b: &FormatInfo) -> Ordering {
+        let fps = |format: &FormatInfo| {
+            match self.fps {
+                // Closer to the preferred rate ranks higher
+                Some(preferred) => -(format.fps.unwrap_or(0).abs_diff(preferred) as i64),
+                None => format.fps.unwrap_or(0) as i64,
+            }
+        };
+
+        let by_height = a.height.cmp(&b.height);
+        match self.mode {
+            SelectionMode::Best =>
+                by_height
+                    .then_with(|| fps(a).cmp(&fps(b)))
+                    .then_with(|| a.bitrate.cmp(&b.bitrate)),
+            SelectionMode::PreferSmallest => {
+                // Formats of unknown size rank below every format of known size
+                let size = |format: &FormatInfo| format.estimated_size().map(std::cmp::Reverse);
+                by_height
+                    .then_with(|| if self.fps.is_some() { fps(a).cmp(&fps(b)) } else { Ordering::Equal })
+                    .then_with(|| size(a).cmp(&size(b)))
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::{ path::Path, time::Duration };
+
+use crate::{ format, Error, FormatPreference, Result, StreamKind, VideoInfo, YouTubeDownloader, USER_AGENT };
+
+impl YouTubeDownloader {
+    /// Saves the frame of a video at a timestamp as an image, e.g. for a custom poster
+    ///
+    /// FFmpeg reads the stream straight from YouTube and seeks with range requests, so only
+    /// the bytes around the timestamp are downloaded. The frame comes from the best
+    /// video-only stream, or the progressive one if there is none. The image format follows
+    /// the extension of `out` (e.g. "poster.jpg", "poster.png").
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `at` - The timestamp of the frame, within the video's duration
+    /// * `out` - The image file to write
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// downloader.extract_frame("dQw4w9WgXcQ", Duration::from_secs(43), "poster.jpg").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn extract_frame(&self, video_id: &str, at: Duration, out: impl AsRef<Path>) -> Result<String> {
+        let json = self.get_player_response(video_id).await?;
+        let info = VideoInfo::from_player_response(&json)?;
+        if info.length_seconds > 0 && at.as_secs_f64() >= (info.length_seconds as f64) {
+            return Err(
+                Error::Api(format!("{:.2?} is past the end of the {}s long video", at, info.length_seconds))
+            );
+        }
+
+        let formats = format::parse_formats(&json);
+        let url = FormatPreference::new(StreamKind::VideoOnly)
+            .select(&formats)
+            .or_else(|| FormatPreference::new(StreamKind::Muxed).select(&formats))
+            .and_then(|format| format.url.clone())
+            .ok_or_else(|| Error::Api("No video stream found".into()))?;
+
+        let output = out.as_ref().to_string_lossy().into_owned();
+        let args = vec![
+            "-user_agent".to_string(),
+            USER_AGENT.to_string(),
+            "-ss".to_string(),
+            format!("{:.3}", at.as_secs_f64()),
+            "-i".to_string(),
+            self.rewrite_url(url),
+            "-frames:v".to_string(),
+            "1".to_string(),
+            "-y".to_string(),
+            output.clone(),
+        ];
+        Self::run_ffmpeg(args, Some(&output), None).await?;
+        Ok(output)
+    }
+}
@@ -0,0 +1,267 @@
+use futures_util::{ stream, StreamExt };
+use reqwest::StatusCode;
+use serde_json::Value;
+
+use crate::{ Error, InnertubeClient, Result, YouTubeDownloader };
+
+/// Metadata of a YouTube video from the player response's `videoDetails`
+#[derive(Debug, Clone)]
+pub struct VideoInfo {
+    pub video_id: String,
+    pub title: String,
+    /// The channel name
+    pub author: String,
+    pub channel_id: String,
+    pub length_seconds: u64,
+    pub view_count: u64,
+    pub keywords: Vec<String>,
+    /// The full description text
+    pub description: String,
+    pub is_live: bool,
+    /// The URL of the largest thumbnail, if any
+    pub thumbnail_url: Option<String>,
+}
+
+impl VideoInfo {
+    pub(crate) fn from_player_response(json: &Value) -> Result<Self> {
+        let details = json
+            .get("videoDetails")
+            .ok_or_else(|| Error::Api("No videoDetails found in response".into()))?;
+
+        let text = |key: &str| details[key].as_str().unwrap_or_default().to_string();
+        let number = |key: &str| {
+            details[key]
+                .as_str()
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(0)
+        };
+
+        Ok(Self {
+            video_id: text("videoId"),
+            title: text("title"),
+            author: text("author"),
+            channel_id: text("channelId"),
+            length_seconds: number("lengthSeconds"),
+            view_count: number("viewCount"),
+            keywords: details["keywords"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|k| k.as_str().map(String::from))
+                .collect(),
+            description: description(json),
+            is_live: details["isLiveContent"].as_bool().unwrap_or(false),
+            thumbnail_url: details["thumbnail"]["thumbnails"]
+                .as_array()
+                .and_then(|thumbnails| thumbnails.last())
+                .and_then(|thumbnail| thumbnail["url"].as_str())
+                .map(String::from),
+        })
+    }
+}
+
+/// Where and whether a video can be played, from the player response's `playabilityStatus`,
+/// `microformat` and `videoDetails`
+#[derive(Debug, Clone)]
+pub struct Availability {
+    /// Whether YouTube reported the video as playable (status "OK")
+    pub playable: bool,
+    /// The playability status (e.g. "OK", "UNPLAYABLE", "LOGIN_REQUIRED")
+    pub status: String,
+    /// The reason given when the video isn't playable
+    pub reason: Option<String>,
+    /// The ISO 3166 codes of the countries the video is available in, empty if not listed
+    pub available_countries: Vec<String>,
+    pub is_unlisted: bool,
+    pub is_family_safe: bool,
+    /// Whether the video is a live stream or a recording of one
+    pub is_live_content: bool,
+    /// Whether the stream is live right now
+    pub is_live: bool,
+    /// Whether the video is a scheduled premiere or stream that hasn't started
+    pub is_upcoming: bool,
+}
+
+impl Availability {
+    pub(crate) fn from_player_response(json: &Value) -> Self {
+        let status = &json["playabilityStatus"];
+        let microformat = &json["microformat"]["playerMicroformatRenderer"];
+        let details = &json["videoDetails"];
+        let flag = |value: &Value| value.as_bool().unwrap_or(false);
+
+        Self {
+            playable: status["status"].as_str() == Some("OK"),
+            status: status["status"].as_str().unwrap_or_default().to_string(),
+            reason: status["reason"].as_str().map(String::from),
+            available_countries: microformat["availableCountries"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|country| country.as_str().map(String::from))
+                .collect(),
+            is_unlisted: flag(&microformat["isUnlisted"]),
+            is_family_safe: flag(&microformat["isFamilySafe"]),
+            is_live_content: flag(&details["isLiveContent"]),
+            is_live: flag(&details["isLive"]),
+            is_upcoming: flag(&details["isUpcoming"]),
+        }
+    }
+
+    /// Returns true if the video is available in a country (e.g. "DE"), assuming it is
+    /// available everywhere when YouTube doesn't list the countries
+    pub fn available_in(&self, country: &str) -> bool {
+        self.available_countries.is_empty() ||
+            self.available_countries.iter().any(|available| available.eq_ignore_ascii_case(country))
+    }
+}
+
+impl YouTubeDownloader {
+    /// Fetches where and whether a video can be played, without failing on unplayable videos
+    ///
+    /// The `microformat` block is only served to the web client, so this makes its own web
+    /// player request rather than going through `get_player_response`, whose playability
+    /// checks would turn an unavailable video into an error.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let availability = downloader.get_availability("dQw4w9WgXcQ").await?;
+    /// if availability.playable && availability.available_in("DE") {
+    ///     downloader.download_video("dQw4w9WgXcQ").await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_availability(&self, video_id: &str) -> Result<Availability> {
+        let json = self.innertube_request(
+            InnertubeClient::Web,
+            "player",
+            serde_json::json!({ "videoId": video_id, "racyCheckOk": true, "contentCheckOk": true })
+        ).await?;
+        Ok(Availability::from_player_response(&json))
+    }
+
+    /// Checks whether a video ID exists with a cheap oEmbed request instead of a player request
+    ///
+    /// Meant for pre-filtering large ID lists. Videos with embedding disabled still count as
+    /// existing, private, removed and malformed IDs don't. A video that exists may still be
+    /// unplayable, see `get_availability` for the details.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// if downloader.exists("dQw4w9WgXcQ").await? {
+    ///     downloader.download_video("dQw4w9WgXcQ").await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn exists(&self, video_id: &str) -> Result<bool> {
+        let video_url = format!("https://www.youtube.com/watch?v={video_id}");
+        let response = self.client
+            .get("https://www.youtube.com/oembed")
+            .query(&[("url", video_url.as_str()), ("format", "json")])
+            .send().await?;
+        match response.status() {
+            StatusCode::OK | StatusCode::UNAUTHORIZED => Ok(true),
+            StatusCode::BAD_REQUEST | StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => Ok(false),
+            status => Err(Error::Api(format!("oEmbed request failed with status: {status}"))),
+        }
+    }
+
+    /// Fetches the metadata of a YouTube video without downloading it
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let info = downloader.get_info("dQw4w9WgXcQ").await?;
+    /// println!("{} by {}", info.title, info.author);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_info(&self, video_id: &str) -> Result<VideoInfo> {
+        let json = self.get_player_response(video_id).await?;
+        VideoInfo::from_player_response(&json)
+    }
+
+    /// Fetches the full description text of a video
+    ///
+    /// Takes `videoDetails.shortDescription`, which despite its name holds the whole text,
+    /// falling back to the microformat description when it is missing.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// println!("{}", downloader.get_description("dQw4w9WgXcQ").await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_description(&self, video_id: &str) -> Result<String> {
+        let json = self.get_player_response(video_id).await?;
+        Ok(description(&json))
+    }
+
+    /// Fetches the metadata of many videos concurrently
+    ///
+    /// At most `concurrency` player requests are in flight at once. The results are returned
+    /// in the same order as `video_ids`, one per ID, so a failing ID doesn't fail the batch.
+    ///
+    /// # Arguments
+    /// * `video_ids` - The YouTube video IDs
+    /// * `concurrency` - The maximum number of simultaneous requests
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for info in downloader.get_info_batch(&["dQw4w9WgXcQ", "9bZkp7q19f0"], 4).await {
+    ///     match info {
+    ///         Ok(info) => println!("{}", info.title),
+    ///         Err(e) => eprintln!("{e}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_info_batch(&self, video_ids: &[&str], concurrency: usize) -> Vec<Result<VideoInfo>> {
+        stream::iter(video_ids)
+            .map(|video_id| self.get_info(video_id))
+            .buffered(concurrency.max(1))
+            .collect().await
+    }
+}
+
+/// Returns the full description of a player response, empty if there is none
+pub(crate) fn description(json: &Value) -> String {
+    json["videoDetails"]["shortDescription"]
+        .as_str()
+        .or_else(|| json["microformat"]["playerMicroformatRenderer"]["description"]["simpleText"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
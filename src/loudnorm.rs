@@ -0,0 +1,104 @@
+use serde_json::Value;
+
+use crate::{ Error, Result };
+
+/// The loudness FFmpeg's `loudnorm` filter normalizes audio to
+///
+/// The defaults of -16 LUFS and -1.5 dBTP suit music and podcasts played on phones and
+/// laptops; broadcast material is usually mastered to -23 LUFS.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ ConversionOptions, LoudnessTarget };
+///
+/// let options = ConversionOptions {
+///     loudness: Some(LoudnessTarget { integrated: -14.0, ..Default::default() }),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessTarget {
+    /// The integrated loudness in LUFS, between -70 and -5
+    pub integrated: f64,
+    /// The maximum true peak in dBTP, between -9 and 0
+    pub true_peak: f64,
+    /// The loudness range in LU, between 1 and 50
+    pub loudness_range: f64,
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self { integrated: -16.0, true_peak: -1.5, loudness_range: 11.0 }
+    }
+}
+
+impl LoudnessTarget {
+    /// Builds the `loudnorm` filter of the measuring first pass
+    pub(crate) fn measure_filter(&self) -> String {
+        format!("{},print_format=json", self.targets())
+    }
+
+    /// Builds the `loudnorm` filter of the second pass, applying the measured values linearly
+    pub(crate) fn apply_filter(&self, measured: &Measurement) -> String {
+        format!(
+            "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+            self.targets(),
+            measured.input_i,
+            measured.input_tp,
+            measured.input_lra,
+            measured.input_thresh,
+            measured.target_offset
+        )
+    }
+
+    fn targets(&self) -> String {
+        format!("loudnorm=I={}:TP={}:LRA={}", self.integrated, self.true_peak, self.loudness_range)
+    }
+}
+
+/// What the first `loudnorm` pass measured on the input
+#[derive(Debug, Clone)]
+pub(crate) struct Measurement {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+    /// The input sample rate, which `loudnorm` would otherwise upsample to 192 kHz
+    pub(crate) sample_rate: Option<String>,
+}
+
+impl Measurement {
+    /// Parses the JSON block `loudnorm` prints at the end of FFmpeg's stderr
+    pub(crate) fn parse(stderr: &str) -> Result<Self> {
+        let missing = || Error::Conversion("FFmpeg printed no loudness measurement".into());
+        let start = stderr.rfind('{').ok_or_else(missing)?;
+        let end = stderr.rfind('}').filter(|end| *end > start).ok_or_else(missing)?;
+        let json: Value = serde_json::from_str(&stderr[start..=end]).map_err(|e| Error::Conversion(e.to_string()))?;
+
+        let field = |key: &str| {
+            json[key]
+                .as_str()
+                .filter(|value| !value.contains("inf"))
+                .map(String::from)
+                .ok_or_else(|| Error::Conversion(format!("Loudness measurement has no usable {key}")))
+        };
+        Ok(Self {
+            input_i: field("input_i")?,
+            input_tp: field("input_tp")?,
+            input_lra: field("input_lra")?,
+            input_thresh: field("input_thresh")?,
+            target_offset: field("target_offset")?,
+            sample_rate: input_sample_rate(stderr),
+        })
+    }
+}
+
+/// Reads the sample rate of the first audio stream from FFmpeg's input description
+fn input_sample_rate(stderr: &str) -> Option<String> {
+    let line = stderr.lines().find(|line| line.contains("Stream #") && line.contains("Audio:"))?;
+    line.split(',')
+        .find_map(|part| part.trim().strip_suffix(" Hz"))
+        .filter(|rate| rate.chars().all(|c| c.is_ascii_digit()))
+        .map(String::from)
+}
@@ -0,0 +1,66 @@
+use std::{ sync::Arc, time::Duration };
+
+use crate::RetryPhase;
+
+/// A structured lifecycle event emitted to the metrics hook
+///
+/// Events carry the timing data the downloader already tracks, so they can be forwarded to
+/// Prometheus, StatsD or any other metrics backend.
+#[derive(Debug, Clone)]
+pub enum TransferEvent {
+    /// The player response for a video was received
+    UrlResolved {
+        video_id: String,
+        /// The innertube client that produced the response (e.g. "ANDROID")
+        client: &'static str,
+        elapsed: Duration,
+    },
+    /// The first byte of a media download arrived
+    FirstByte {
+        path: String,
+        ttfb: Duration,
+    },
+    /// A media download finished writing to disk
+    Completed {
+        path: String,
+        /// The bytes received by this transfer, excluding any resumed prefix
+        bytes: u64,
+        duration: Duration,
+        resume: ResumeOutcome,
+    },
+    /// A request is about to be retried
+    Retry {
+        phase: RetryPhase,
+        attempt: u32,
+        error: String,
+    },
+    /// A finished file was moved to its final path
+    Moved {
+        from: String,
+        to: String,
+        /// Whether the destination was on another filesystem, so the file had to be copied
+        copied: bool,
+    },
+    /// A media download failed
+    Failed {
+        path: String,
+        error: String,
+    },
+}
+
+/// How a download treated a partial file left over from an earlier attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeOutcome {
+    /// There was nothing to resume, the file was written from the start
+    Fresh,
+    /// The server honoured the Range request and the file was appended to from `offset`
+    Appended {
+        offset: u64,
+    },
+    /// The server ignored the Range request, so the file was truncated and downloaded again
+    Restarted,
+    /// The partial file already held the whole stream
+    AlreadyComplete,
+}
+
+pub(crate) type MetricsHook = Arc<dyn Fn(&TransferEvent) + Send + Sync>;
@@ -0,0 +1,111 @@
+use futures_util::StreamExt;
+use memmap2::{ Mmap, MmapMut };
+use std::{ path::Path, time::Instant };
+use tokio::{ fs::{ File, OpenOptions }, task::spawn_blocking };
+
+use crate::{
+    progress::ProgressThrottle,
+    DownloadProgress,
+    Error,
+    ProgressEvent,
+    Result,
+    YouTubeDownloader,
+};
+
+impl YouTubeDownloader {
+    /// Downloads a YouTube video into a memory-mapped file, returning a read-only map of it
+    ///
+    /// When the server reports the size, the file is preallocated to it and every chunk is
+    /// copied straight into the mapping, which is flushed to disk before it is returned.
+    /// Without a known size the video is downloaded the usual way and mapped afterwards.
+    /// The map stays valid as long as nothing else truncates or rewrites the file.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `path` - The file to write, replaced if it exists
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::path::Path;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let map = downloader.download_to_mmap("dQw4w9WgXcQ", Path::new("video.mp4")).await?;
+    /// println!("First bytes: {:?}", &map[..8]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_to_mmap(&self, video_id: &str, path: &Path) -> Result<Mmap> {
+        let start_time = Instant::now();
+        let url = self.get_video_url(video_id).await?;
+        let path_str = path.to_string_lossy().into_owned();
+        let pb = Self::progress_bar()?;
+
+        let res = self.media_request(&self.rewrite_url(url.clone())).send().await?;
+        if !res.status().is_success() {
+            return Err(Error::Api(format!("Download request failed with status: {}", res.status())));
+        }
+        let Some(total_size) = res.content_length().filter(|length| *length > 0) else {
+            drop(res);
+            self.download_url(&url, &path_str, &pb).await?;
+            pb.finish_with_message("Download complete");
+            let file = File::open(path).await?.into_std().await;
+            // Safety: the file was just written by us and is not modified while mapped
+            return Ok(spawn_blocking(move || unsafe { Mmap::map(&file) }).await.map_err(std::io::Error::other)??);
+        };
+
+        // Preallocated to the full size, so a partial file would pass for a complete one
+        let mut partial = PartialFile(Some(path));
+        let file = OpenOptions::new().read(true).write(true).create(true).truncate(true).open(path).await?;
+        file.set_len(total_size).await?;
+        let file = file.into_std().await;
+        // Safety: the file was just created by us and is only written through this mapping
+        let mut map = spawn_blocking(move || unsafe { MmapMut::map_mut(&file) }).await.map_err(std::io::Error::other)??;
+        pb.set_length(total_size);
+
+        let mut stream = res.bytes_stream();
+        let throttle = ProgressThrottle::new(self.progress_interval);
+        let mut position = 0usize;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| self.stalled(e.into()))?;
+            let end = position + chunk.len();
+            if end > map.len() {
+                return Err(Error::Api(format!("Received more than the announced {total_size} bytes")));
+            }
+            map[position..end].copy_from_slice(&chunk);
+            position = end;
+            pb.set_position(position as u64);
+            self.govern(chunk.len() as u64).await;
+            if throttle.ready() {
+                self.report(
+                    ProgressEvent::Downloading(DownloadProgress::new(position as u64, Some(total_size), &pb))
+                );
+            }
+        }
+        if throttle.take_pending() {
+            self.report(ProgressEvent::Downloading(DownloadProgress::new(position as u64, Some(total_size), &pb)));
+        }
+        crate::check_size(&path_str, position as u64, None, Some(total_size))?;
+
+        let map = spawn_blocking(move || {
+            map.flush()?;
+            map.make_read_only()
+        }).await.map_err(std::io::Error::other)??;
+        partial.0 = None;
+        pb.finish_with_message("Download complete");
+        println!("Download complete! Total time: {:.2?}", start_time.elapsed());
+        Ok(map)
+    }
+}
+
+/// Removes a preallocated download that failed or was cancelled before it was complete
+struct PartialFile<'a>(Option<&'a Path>);
+
+impl Drop for PartialFile<'_> {
+    fn drop(&mut self) {
+        if let Some(path) = self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    hash::{ BuildHasher, RandomState },
+    sync::{ Arc, Mutex, atomic::{ AtomicU64, Ordering } },
+    time::{ Duration, Instant },
+};
+
+use crate::YouTubeDownloader;
+
+/// The rate cap a slow-started download begins with, in bytes per second
+const INITIAL_RATE: f64 = 256.0 * 1024.0;
+
+/// Paces the start of a download with a rate cap doubling every second
+///
+/// Like TCP slow start, the transfer ramps up instead of requesting at full speed from the
+/// first byte, which triggers throttling on some edges. The cap is lifted once `ramp` has
+/// passed.
+pub(crate) struct SlowStart {
+    ramp: Duration,
+    start: Instant,
+    received: u64,
+}
+
+impl SlowStart {
+    pub(crate) fn new(ramp: Duration) -> Self {
+        Self { ramp, start: Instant::now(), received: 0 }
+    }
+
+    /// Counts `bytes` more received bytes, returning how long to wait to stay under the cap
+    pub(crate) fn delay(&mut self, bytes: u64) -> Option<Duration> {
+        let elapsed = self.start.elapsed();
+        if elapsed >= self.ramp {
+            return None;
+        }
+        self.received += bytes;
+
+        // With the cap at INITIAL_RATE * 2^t, the bytes allowed by time t add up to
+        // INITIAL_RATE * (2^t - 1) / ln 2, solved here for t
+        let allowed_at = ((self.received as f64) * std::f64::consts::LN_2 / INITIAL_RATE + 1.0).log2();
+        let allowed_at = Duration::from_secs_f64(allowed_at).min(self.ramp);
+        allowed_at.checked_sub(elapsed).filter(|delay| !delay.is_zero())
+    }
+}
+
+/// A byte-rate ceiling shared by every download it is handed to
+///
+/// A token bucket holding up to one second worth of bytes: each received chunk takes its
+/// size from the bucket, and downloads wait while it is in debt. Clones share the bucket, so
+/// giving clones to several `YouTubeDownloader`s caps their combined rate, as does the
+/// parallel and batch concurrency of a single downloader.
+///
+/// # Example
+/// ```
+/// use rustytdown::{ RateGovernor, YouTubeDownloader };
+///
+/// let governor = RateGovernor::new(5 * 1024 * 1024);
+/// let music = YouTubeDownloader::builder().rate_governor(governor.clone()).build().unwrap();
+/// let videos = YouTubeDownloader::builder().rate_governor(governor.clone()).build().unwrap();
+/// governor.set_rate(2 * 1024 * 1024);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateGovernor {
+    rate: Arc<AtomicU64>,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl RateGovernor {
+    /// Creates a governor capping the combined rate at `bytes_per_second`
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            rate: Arc::new(AtomicU64::new(bytes_per_second.max(1))),
+            bucket: Arc::new(Mutex::new(Bucket { tokens: 0.0, refilled: Instant::now() })),
+        }
+    }
+
+    /// Returns the current ceiling in bytes per second
+    pub fn rate(&self) -> u64 {
+        self.rate.load(Ordering::Relaxed)
+    }
+
+    /// Changes the ceiling of every download sharing this governor, taking effect on their
+    /// next chunk
+    pub fn set_rate(&self, bytes_per_second: u64) {
+        self.rate.store(bytes_per_second.max(1), Ordering::Relaxed);
+    }
+
+    /// Takes `bytes` from the bucket, returning how long to wait to pay off the debt
+    pub(crate) fn delay(&self, bytes: u64) -> Option<Duration> {
+        let rate = self.rate() as f64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refill).min(rate) - (bytes as f64);
+        bucket.refilled = now;
+        (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / rate))
+    }
+}
+
+impl YouTubeDownloader {
+    /// Waits as long as the rate governor demands after receiving `bytes`
+    pub(crate) async fn govern(&self, bytes: u64) {
+        if let Some(delay) = self.rate_governor.as_ref().and_then(|governor| governor.delay(bytes)) {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Waits a random delay of up to `request_jitter`, staggering the player requests of a batch
+    pub(crate) async fn jitter(&self) {
+        let Some(max) = self.request_jitter.filter(|max| !max.is_zero()) else {
+            return;
+        };
+        // Every `RandomState` is keyed differently, which is random enough to spread requests
+        let random = RandomState::new().hash_one(Instant::now());
+        let delay = max.mul_f64((random as f64) / (u64::MAX as f64));
+        tokio::time::sleep(delay).await;
+    }
+}
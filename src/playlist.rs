@@ -0,0 +1,215 @@
+use futures_util::{ future, stream, Stream, StreamExt, TryStreamExt };
+use serde_json::Value;
+use std::ops::Range;
+
+use crate::{ BatchOptions, DownloadReport, Error, InnertubeClient, Result, YouTubeDownloader };
+
+/// A video listed in a playlist
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub video_id: String,
+    pub title: String,
+    /// The zero-based position in the playlist
+    pub index: usize,
+    /// The duration, missing for upcoming premieres and live streams
+    pub length_seconds: Option<u64>,
+}
+
+/// Where the next playlist page comes from
+enum Page {
+    First,
+    Continuation(String),
+}
+
+impl YouTubeDownloader {
+    /// Fetches every entry of a playlist
+    ///
+    /// Collects `playlist_entries_stream`, failing on the first page that can't be fetched.
+    ///
+    /// # Arguments
+    /// * `playlist_id` - The YouTube playlist ID (e.g. "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for entry in downloader.playlist_entries("PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI").await? {
+    ///     println!("{} {}", entry.video_id, entry.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn playlist_entries(&self, playlist_id: &str) -> Result<Vec<PlaylistEntry>> {
+        self.playlist_entries_stream(playlist_id).try_collect().await
+    }
+
+    /// Fetches the entries of a playlist whose zero-based index lies in `range`
+    ///
+    /// Pages past the end of the range aren't requested, so taking the first hundred entries
+    /// of a huge channel playlist only costs the first pages.
+    ///
+    /// # Arguments
+    /// * `playlist_id` - The YouTube playlist ID (e.g. "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI")
+    /// * `range` - The indices to keep, `PlaylistEntry::index` being in it
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let entries = downloader.playlist_entries_range("PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI", 50..100).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn playlist_entries_range(&self, playlist_id: &str, range: Range<usize>) -> Result<Vec<PlaylistEntry>> {
+        self.playlist_entries_stream(playlist_id)
+            .try_skip_while(|entry| future::ready(Ok(entry.index < range.start)))
+            .try_take_while(|entry| future::ready(Ok(entry.index < range.end)))
+            .try_collect().await
+    }
+
+    /// Downloads the entries of a playlist whose zero-based index lies in `range`
+    ///
+    /// The entries are enumerated with `playlist_entries_range` and downloaded with
+    /// `download_batch`, so `options` sets the quality and side files like for any batch. A
+    /// playlist page failing to load fails the whole call, a failing video only its own
+    /// result. Useful to resume huge playlists in chunks.
+    ///
+    /// # Arguments
+    /// * `playlist_id` - The YouTube playlist ID (e.g. "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI")
+    /// * `range` - The indices to download, `PlaylistEntry::index` being in it
+    /// * `options` - The artifacts to save and where to put them
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ BatchOptions, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let options = BatchOptions::new("archive").concurrency(4);
+    /// let reports = downloader.download_playlist_range("PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI", 50..100, &options).await?;
+    /// for report in reports {
+    ///     match report {
+    ///         Ok(report) => println!("{}", report.path),
+    ///         Err(e) => eprintln!("{e}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_playlist_range(
+        &self,
+        playlist_id: &str,
+        range: Range<usize>,
+        options: &BatchOptions
+    ) -> Result<Vec<Result<DownloadReport>>> {
+        let entries = self.playlist_entries_range(playlist_id, range).await?;
+        let video_ids: Vec<&str> = entries
+            .iter()
+            .map(|entry| entry.video_id.as_str())
+            .collect();
+        Ok(self.download_batch(&video_ids, options).await)
+    }
+
+    /// Yields the entries of a playlist page by page as they are fetched
+    ///
+    /// Each continuation page is only requested once the entries of the previous one were
+    /// consumed, so downloads can start on the first page of a huge playlist while nothing
+    /// beyond the current page is held in memory. A failed page request is yielded as an
+    /// error and ends the stream.
+    ///
+    /// # Arguments
+    /// * `playlist_id` - The YouTube playlist ID (e.g. "PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use futures_util::StreamExt;
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let mut entries = std::pin::pin!(downloader.playlist_entries_stream("PLFgquLnL59alCl_2TQvOiD5Vgm1hCaGSI"));
+    /// while let Some(entry) = entries.next().await {
+    ///     downloader.download_video(&entry?.video_id).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn playlist_entries_stream<'a>(
+        &'a self,
+        playlist_id: &'a str
+    ) -> impl Stream<Item = Result<PlaylistEntry>> + 'a {
+        stream::unfold(Some((Page::First, 0)), move |state| async move {
+            let (page, index) = state?;
+            let (body, first) = match page {
+                Page::First => (serde_json::json!({ "browseId": format!("VL{playlist_id}") }), true),
+                Page::Continuation(token) => (serde_json::json!({ "continuation": token }), false),
+            };
+
+            let response = match self.innertube_request(InnertubeClient::Web, "browse", body).await {
+                Ok(response) => response,
+                Err(e) => {
+                    return Some((vec![Err(e)], None));
+                }
+            };
+            let Some(items) = page_items(&response, first) else {
+                let error = Error::Api(format!("No playlist found for ID {playlist_id}"));
+                return Some((vec![Err(error)], None));
+            };
+
+            let mut entries = Vec::new();
+            let mut next_token = None;
+            for item in items {
+                if let Some(entry) = parse_entry(item, index + entries.len()) {
+                    entries.push(Ok(entry));
+                } else if let Some(token) = continuation_token(item) {
+                    next_token = Some(token);
+                }
+            }
+
+            let next_index = index + entries.len();
+            Some((entries, next_token.map(|token| (Page::Continuation(token), next_index))))
+        }).flat_map(stream::iter)
+    }
+}
+
+/// Returns the items of a browse response, `None` if the first page holds no playlist
+fn page_items(page: &Value, first: bool) -> Option<&Vec<Value>> {
+    if first {
+        return page["contents"]["twoColumnBrowseResultsRenderer"]["tabs"][0]["tabRenderer"]["content"]
+            ["sectionListRenderer"]["contents"][0]["itemSectionRenderer"]["contents"][0]
+            ["playlistVideoListRenderer"]["contents"].as_array();
+    }
+    static EMPTY: Vec<Value> = Vec::new();
+    Some(
+        page["onResponseReceivedActions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find_map(|action| action["appendContinuationItemsAction"]["continuationItems"].as_array())
+            .unwrap_or(&EMPTY)
+    )
+}
+
+fn parse_entry(item: &Value, index: usize) -> Option<PlaylistEntry> {
+    let renderer = item.get("playlistVideoRenderer")?;
+    Some(PlaylistEntry {
+        video_id: renderer["videoId"].as_str()?.to_string(),
+        title: renderer["title"]["runs"][0]["text"]
+            .as_str()
+            .or_else(|| renderer["title"]["simpleText"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        index,
+        length_seconds: renderer["lengthSeconds"].as_str().and_then(|l| l.parse().ok()),
+    })
+}
+
+fn continuation_token(item: &Value) -> Option<String> {
+    item["continuationItemRenderer"]["continuationEndpoint"]["continuationCommand"]["token"]
+        .as_str()
+        .map(String::from)
+}
@@ -0,0 +1,64 @@
+use bytes::{ Bytes, BytesMut };
+use futures_util::{ stream, Stream, StreamExt };
+use std::{ pin::Pin, sync::Arc };
+use tokio::sync::{ OwnedSemaphorePermit, Semaphore, mpsc };
+
+use crate::Error;
+
+pub(crate) type ByteStream = Pin<Box<dyn Stream<Item = std::result::Result<Bytes, Error>> + Send>>;
+
+/// Keeps reading `upstream` ahead of the consumer until `capacity` bytes are buffered
+///
+/// Every buffered chunk holds permits of a byte semaphore until the consumer takes it, so a
+/// slow reader pauses the download instead of growing the buffer. A chunk larger than the
+/// whole capacity is still let through on its own.
+pub(crate) fn prefetch(mut upstream: ByteStream, capacity: usize) -> ByteStream {
+    let capacity = capacity.clamp(1, Semaphore::MAX_PERMITS) as u32;
+    let budget = Arc::new(Semaphore::new(capacity as usize));
+    let (tx, rx) = mpsc::unbounded_channel::<(std::result::Result<Bytes, Error>, OwnedSemaphorePermit)>();
+
+    tokio::spawn(async move {
+        while let Some(item) = upstream.next().await {
+            let size = item.as_ref().map_or(0, |chunk| chunk.len()).clamp(1, capacity as usize) as u32;
+            let Ok(permit) = budget.clone().acquire_many_owned(size).await else {
+                break;
+            };
+            // The consumer hung up, stop downloading
+            if tx.send((item, permit)).is_err() {
+                break;
+            }
+        }
+    });
+
+    Box::pin(
+        stream::unfold(rx, |mut rx| async move {
+            // The permit is released as soon as the chunk is handed out
+            let (item, _permit) = rx.recv().await?;
+            Some((item, rx))
+        })
+    )
+}
+
+/// Regroups the chunks of `upstream` into parts of exactly `size` bytes, the last one
+/// holding what is left
+pub(crate) fn rechunk(upstream: ByteStream, size: usize) -> ByteStream {
+    let size = size.max(1);
+    Box::pin(
+        stream::unfold(Some((upstream, BytesMut::new())), move |state| async move {
+            let (mut upstream, mut buffer) = state?;
+            while buffer.len() < size {
+                match upstream.next().await {
+                    Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                    Some(Err(e)) => {
+                        return Some((Err(e), None));
+                    }
+                    None => {
+                        return (!buffer.is_empty()).then(|| (Ok(buffer.freeze()), None));
+                    }
+                }
+            }
+            let part = buffer.split_to(size).freeze();
+            Some((Ok(part), Some((upstream, buffer))))
+        })
+    )
+}
@@ -0,0 +1,226 @@
+use std::{
+    sync::{ Arc, Mutex, atomic::{ AtomicBool, Ordering } },
+    time::{ Duration, Instant },
+};
+use indicatif::ProgressBar;
+use tokio::{ sync::mpsc, task::JoinHandle };
+
+use crate::{ Result, YouTubeDownloader };
+
+/// How many events `download_video_channel` buffers for a lagging receiver
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Byte progress of a running download
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    /// The bytes written so far, including any resumed prefix
+    pub downloaded: u64,
+    /// The total size in bytes, if the server reported it
+    pub total: Option<u64>,
+    /// The estimated time until the download finishes at the current rate, `None` while the
+    /// total or the rate is unknown
+    pub eta: Option<Duration>,
+}
+
+impl DownloadProgress {
+    /// Captures the progress of a download, estimating the time left from the rate `pb` measured
+    pub(crate) fn new(downloaded: u64, total: Option<u64>, pb: &ProgressBar) -> Self {
+        let eta = total.and_then(|total| {
+            let remaining = total.saturating_sub(downloaded);
+            let rate = pb.per_sec();
+            if remaining == 0 {
+                Some(Duration::ZERO)
+            } else if rate > 0.0 && rate.is_finite() {
+                Some(Duration::from_secs_f64((remaining as f64) / rate))
+            } else {
+                None
+            }
+        });
+        Self { downloaded, total, eta }
+    }
+}
+
+/// A lifecycle or progress event delivered to the progress callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// The player request for a video is being made
+    ResolvingUrl {
+        video_id: String,
+    },
+    /// A chunk of media was written to disk
+    Downloading(DownloadProgress),
+    /// FFmpeg conversion of the downloaded media started
+    ConvertingStarted,
+    /// FFmpeg conversion of the downloaded media finished
+    ConvertingFinished,
+    /// A final output file is ready
+    Completed {
+        path: String,
+    },
+}
+
+/// An update sent over the channel of `download_video_with_channel`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressMessage {
+    /// A chunk of media was written to disk
+    Progress(DownloadProgress),
+    /// The download finished, with the path of the video
+    Completed(String),
+    /// The download failed, with the error message
+    Failed(String),
+}
+
+pub(crate) type ProgressCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+/// Rate-limits the `Downloading` events of one download to the configured interval
+pub(crate) struct ProgressThrottle {
+    interval: Option<Duration>,
+    last: Mutex<Option<Instant>>,
+    pending: AtomicBool,
+}
+
+impl ProgressThrottle {
+    pub(crate) fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            last: Mutex::new(None),
+            pending: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns true if an update is due now, otherwise remembers that one was skipped
+    pub(crate) fn ready(&self) -> bool {
+        let Some(interval) = self.interval else {
+            return true;
+        };
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        if last.is_some_and(|last| now.duration_since(last) < interval) {
+            self.pending.store(true, Ordering::Relaxed);
+            return false;
+        }
+        *last = Some(now);
+        self.pending.store(false, Ordering::Relaxed);
+        true
+    }
+
+    /// Returns true if the latest update was skipped, so the final state still gets reported
+    pub(crate) fn take_pending(&self) -> bool {
+        self.pending.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl YouTubeDownloader {
+    /// Downloads a YouTube video like `download_video` on a spawned task, delivering progress
+    /// events over a channel
+    ///
+    /// An alternative to the `on_progress` callback that fans out easily to async consumers
+    /// such as WebSocket clients. A configured callback still receives every event. The
+    /// channel holds up to 256 events, further events are dropped while the receiver lags,
+    /// and dropping the receiver doesn't stop the download. The join handle yields the path
+    /// of the video.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let (mut events, handle) = downloader.download_video_channel("dQw4w9WgXcQ");
+    /// while let Some(event) = events.recv().await {
+    ///     println!("{event:?}");
+    /// }
+    /// let video_path = handle.await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn download_video_channel(
+        &self,
+        video_id: &str
+    ) -> (mpsc::Receiver<ProgressEvent>, JoinHandle<Result<String>>) {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let callback = self.on_progress.clone();
+
+        let mut downloader = self.clone();
+        downloader.on_progress = Some(
+            Arc::new(move |event: &ProgressEvent| {
+                if let Some(callback) = &callback {
+                    callback(event);
+                }
+                let _ = tx.try_send(event.clone());
+            })
+        );
+
+        let video_id = video_id.to_string();
+        let handle = tokio::spawn(async move { downloader.download_video(&video_id).await });
+        (rx, handle)
+    }
+
+    /// Downloads a YouTube video like `download_video`, sending byte progress and the outcome to `tx`
+    ///
+    /// Meant for GUI frameworks consuming updates on their UI thread, e.g. with
+    /// `Receiver::blocking_recv`. A configured `on_progress` callback still receives every
+    /// event. Progress updates are dropped rather than awaited while the channel is full, so
+    /// a busy UI never stalls the download. Once the download finished or failed, a final
+    /// `Completed` or `Failed` message is sent, waiting for room in the channel, the sender is
+    /// dropped and the path or error is returned.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    /// * `tx` - The channel receiving the progress updates
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ ProgressMessage, YouTubeDownloader };
+    /// use tokio::sync::mpsc;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let (tx, mut rx) = mpsc::channel::<ProgressMessage>(64);
+    /// std::thread::spawn(move || {
+    ///     while let Some(message) = rx.blocking_recv() {
+    ///         match message {
+    ///             ProgressMessage::Progress(progress) => {
+    ///                 println!("{} of {:?} bytes", progress.downloaded, progress.total)
+    ///             }
+    ///             ProgressMessage::Completed(path) => println!("Saved to {path}"),
+    ///             ProgressMessage::Failed(error) => eprintln!("Download failed: {error}"),
+    ///         }
+    ///     }
+    /// });
+    /// let video_path = downloader.download_video_with_channel("dQw4w9WgXcQ", tx).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_video_with_channel(
+        &self,
+        video_id: &str,
+        tx: mpsc::Sender<ProgressMessage>
+    ) -> Result<String> {
+        let callback = self.on_progress.clone();
+        let progress_tx = tx.clone();
+
+        let mut downloader = self.clone();
+        downloader.on_progress = Some(
+            Arc::new(move |event: &ProgressEvent| {
+                if let Some(callback) = &callback {
+                    callback(event);
+                }
+                if let ProgressEvent::Downloading(progress) = event {
+                    let _ = progress_tx.try_send(ProgressMessage::Progress(*progress));
+                }
+            })
+        );
+
+        let result = downloader.download_video(video_id).await;
+        let message = match &result {
+            Ok(path) => ProgressMessage::Completed(path.clone()),
+            Err(e) => ProgressMessage::Failed(e.to_string()),
+        };
+        let _ = tx.send(message).await;
+        result
+    }
+}
@@ -0,0 +1,42 @@
+use serde_json::Value;
+use std::{ collections::HashMap, path::PathBuf };
+
+use crate::{ Result, YouTubeDownloader };
+
+/// Where player responses come from and go to when the `replay` feature is enabled
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplayConfig {
+    /// Responses fed in memory, keyed by video ID
+    pub(crate) responses: HashMap<String, Value>,
+    /// A directory of `<video_id>.json` files to replay instead of hitting the network
+    pub(crate) replay_dir: Option<PathBuf>,
+    /// A directory live responses are saved to as `<video_id>.json`
+    pub(crate) record_dir: Option<PathBuf>,
+}
+
+impl YouTubeDownloader {
+    /// Returns a recorded player response for `video_id`, if one was configured
+    pub(crate) async fn replayed_player_response(&self, video_id: &str) -> Result<Option<Value>> {
+        if let Some(response) = self.replay.responses.get(video_id) {
+            return Ok(Some(response.clone()));
+        }
+        if let Some(dir) = &self.replay.replay_dir {
+            let path = dir.join(format!("{video_id}.json"));
+            if tokio::fs::try_exists(&path).await? {
+                let json = tokio::fs::read(&path).await?;
+                return Ok(Some(serde_json::from_slice(&json).map_err(std::io::Error::from)?));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Saves a live player response if recording is enabled
+    pub(crate) async fn record_player_response(&self, video_id: &str, response: &Value) -> Result<()> {
+        if let Some(dir) = &self.replay.record_dir {
+            tokio::fs::create_dir_all(dir).await?;
+            let json = serde_json::to_vec_pretty(response).map_err(std::io::Error::from)?;
+            tokio::fs::write(dir.join(format!("{video_id}.json")), json).await?;
+        }
+        Ok(())
+    }
+}
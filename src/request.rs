@@ -0,0 +1,294 @@
+use std::{ path::PathBuf, time::{ Duration, Instant } };
+
+use crate::{
+    ConversionOptions,
+    Error,
+    FormatInfo,
+    FormatPreference,
+    Result,
+    StreamKind,
+    YouTubeDownloader,
+};
+
+/// What a `DownloadRequest` should produce
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MediaKind {
+    /// A progressive format carrying both video and audio
+    #[default]
+    Video,
+    /// Audio converted with FFmpeg according to the request's `ConversionOptions`
+    Audio,
+    /// The best video-only and audio-only streams merged with FFmpeg
+    Best,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    VideoId(String),
+    Url(String),
+}
+
+/// A description of a download for `YouTubeDownloader::download`
+///
+/// # Example
+/// ```
+/// use rustytdown::{ DownloadRequest, FormatPreference, MediaKind };
+///
+/// let request = DownloadRequest::new("dQw4w9WgXcQ")
+///     .kind(MediaKind::Best)
+///     .preference(FormatPreference::default().max_height(1080))
+///     .output("rickroll.mp4");
+/// ```
+#[derive(Debug, Clone)]
+pub struct DownloadRequest {
+    source: Source,
+    kind: MediaKind,
+    preference: FormatPreference,
+    conversion: ConversionOptions,
+    output: Option<PathBuf>,
+    embed_chapters: bool,
+    #[cfg(feature = "sponsorblock")]
+    skip_categories: Vec<String>,
+}
+
+impl DownloadRequest {
+    /// Creates a request for a YouTube video ID (e.g. "dQw4w9WgXcQ")
+    pub fn new(video_id: impl Into<String>) -> Self {
+        Self {
+            source: Source::VideoId(video_id.into()),
+            kind: MediaKind::default(),
+            preference: FormatPreference::default(),
+            conversion: ConversionOptions::default(),
+            output: None,
+            embed_chapters: false,
+            #[cfg(feature = "sponsorblock")]
+            skip_categories: Vec::new(),
+        }
+    }
+
+    /// Creates a request for an already resolved direct media URL
+    ///
+    /// No format selection happens for URL requests, and `MediaKind::Best` is rejected
+    /// because it needs two streams.
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self {
+            source: Source::Url(url.into()),
+            kind: MediaKind::default(),
+            preference: FormatPreference::default(),
+            conversion: ConversionOptions::default(),
+            output: None,
+            embed_chapters: false,
+            #[cfg(feature = "sponsorblock")]
+            skip_categories: Vec::new(),
+        }
+    }
+
+    /// Sets what the download should produce
+    pub fn kind(mut self, kind: MediaKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the quality constraints for format selection
+    ///
+    /// The stream kind of the preference is ignored, it follows from the `MediaKind`. For
+    /// `MediaKind::Best` the preference applies to the video stream.
+    pub fn preference(mut self, preference: FormatPreference) -> Self {
+        self.preference = preference;
+        self
+    }
+
+    /// Sets the FFmpeg conversion options used for `MediaKind::Audio`
+    pub fn conversion(mut self, conversion: ConversionOptions) -> Self {
+        self.conversion = conversion;
+        self
+    }
+
+    /// Embeds the chapters of the video as chapter metadata into the output file
+    ///
+    /// Players such as VLC and mpv then offer chapter navigation. Needs FFmpeg, and does
+    /// nothing for URL requests or videos without chapters.
+    pub fn embed_chapters(mut self, enabled: bool) -> Self {
+        self.embed_chapters = enabled;
+        self
+    }
+
+    /// Cuts the SponsorBlock segments of these categories out of the output after downloading
+    ///
+    /// Typical categories are "sponsor", "selfpromo", "interaction", "intro", "outro",
+    /// "preview", "music_offtopic" and "filler"; an empty list keeps the media untouched.
+    /// Overlapping segments are merged and the remaining parts joined without re-encoding.
+    /// Needs FFmpeg, does nothing for URL requests, and embedded chapters are not shifted.
+    #[cfg(feature = "sponsorblock")]
+    pub fn skip_sponsor_segments(mut self, categories: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.skip_categories = categories.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the output path, defaulting to the video ID with a matching extension
+    ///
+    /// An existing directory keeps the default file name inside that directory.
+    pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+}
+
+/// The outcome of `YouTubeDownloader::download`
+#[derive(Debug, Clone)]
+pub struct DownloadReport {
+    /// The path of the final file
+    pub path: String,
+    /// The selected format, or the video format for `MediaKind::Best`
+    ///
+    /// This is `None` when the request didn't go through format selection.
+    pub format: Option<FormatInfo>,
+    /// The width in pixels of the downloaded video, taken from the selected format
+    pub width: Option<u32>,
+    /// The height in pixels of the downloaded video, taken from the selected format
+    pub height: Option<u32>,
+    /// The width divided by the height (e.g. 1.777 for 16:9), saving a probe of the file
+    pub aspect_ratio: Option<f64>,
+    /// What the output actually carries, `None` for URL requests where the format is unknown
+    ///
+    /// A `SourceKind::VideoOnly` output is a silent video.
+    pub source_kind: Option<SourceKind>,
+    /// The total time the download took
+    pub duration: Duration,
+}
+
+/// The nature of a downloaded output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    /// A progressive format carrying both video and audio
+    Progressive,
+    /// Separate video and audio streams merged with FFmpeg
+    MuxedAdaptive,
+    /// Video without an audio stream
+    VideoOnly,
+    /// Audio without a video stream
+    AudioOnly,
+}
+
+impl SourceKind {
+    /// Classifies a single downloaded format by the streams it carries
+    pub fn of(format: &FormatInfo) -> Self {
+        match (format.has_video(), format.has_audio()) {
+            (true, true) => Self::Progressive,
+            (true, false) => Self::VideoOnly,
+            (false, _) => Self::AudioOnly,
+        }
+    }
+}
+
+impl YouTubeDownloader {
+    /// Runs a download described by a `DownloadRequest`
+    ///
+    /// This is the single entrypoint covering what `download_video`, `download_and_convert`
+    /// and `download_merged` do, with control over quality and output path.
+    ///
+    /// # Arguments
+    /// * `request` - The description of the download
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::{ DownloadRequest, MediaKind, YouTubeDownloader };
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let report = downloader.download(DownloadRequest::new("dQw4w9WgXcQ").kind(MediaKind::Best)).await?;
+    /// println!("Saved {} in {:.2?}", report.path, report.duration);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download(&self, request: DownloadRequest) -> Result<DownloadReport> {
+        let start_time = Instant::now();
+        if let Source::VideoId(video_id) = &request.source {
+            self.check_archive(video_id).await?;
+        }
+        let (output, output_dir) = match request.output.as_deref() {
+            Some(dir) if dir.is_dir() => (None, Some(dir)),
+            output => (output, None),
+        };
+        let default_path = |name: String| output_dir.map_or_else(|| PathBuf::from(&name), |dir| dir.join(&name));
+
+        let (path, format, source_kind) = match (&request.source, request.kind) {
+            (Source::Url(_), MediaKind::Best) => {
+                return Err(Error::Api("MediaKind::Best cannot be downloaded from a single URL".into()));
+            }
+            (Source::Url(url), MediaKind::Audio) => {
+                let default_output = default_path(format!("audio.{}", request.conversion.format.extension()));
+                let output = output.unwrap_or(&default_output);
+                (self.convert_url(url, output, &request.conversion, None).await?, None, Some(SourceKind::AudioOnly))
+            }
+            (Source::Url(url), MediaKind::Video) => {
+                let default_output = default_path("video.mp4".into());
+                let output = output.unwrap_or(&default_output);
+                (self.download_video_from_url(url, output).await?, None, None)
+            }
+            (Source::VideoId(video_id), MediaKind::Audio) => {
+                let (url, conversion) = self.convert_source_url(video_id, &request.conversion).await?;
+                let default_output = default_path(format!("{video_id}.{}", conversion.format.extension()));
+                let output = output.unwrap_or(&default_output);
+                (self.convert_url(&url, output, &conversion, None).await?, None, Some(SourceKind::AudioOnly))
+            }
+            (Source::VideoId(video_id), MediaKind::Video) => {
+                let preference = request.preference.clone().with_kind(StreamKind::Muxed);
+                let format = self.select_format(video_id, &preference).await?;
+                let default_output = default_path(format!("{video_id}.{}", format.extension()));
+                let output = output.unwrap_or(&default_output);
+                let source_kind = SourceKind::of(&format);
+                (self.download_format_to(&format, output).await?, Some(format), Some(source_kind))
+            }
+            (Source::VideoId(video_id), MediaKind::Best) => {
+                let formats = self.list_formats(video_id).await?;
+                let preference = request.preference.clone().with_kind(StreamKind::VideoOnly);
+                let video = preference.select(&formats).ok_or_else(|| {
+                    preference
+                        .codec_error(&formats)
+                        .unwrap_or_else(|| Error::Api("No video-only stream satisfies the requested preference".into()))
+                })?;
+                let audio = FormatPreference::new(StreamKind::AudioOnly)
+                    .select(&formats)
+                    .ok_or_else(|| Error::Api("No audio-only stream found".into()))?;
+                let default_output = default_path(
+                    format!("{video_id}.{}", crate::format::merged_extension(video, audio))
+                );
+                let output = output.unwrap_or(&default_output);
+                let path = self.merge_formats(video, audio, output).await?;
+                (path, Some(video.clone()), Some(SourceKind::MuxedAdaptive))
+            }
+        };
+
+        if let Source::VideoId(video_id) = &request.source {
+            #[cfg(feature = "sponsorblock")]
+            if !request.skip_categories.is_empty() {
+                self.cut_sponsor_segments(video_id, &path, &request.skip_categories).await?;
+            }
+            if request.embed_chapters {
+                let chapters = self.get_chapters(video_id).await?;
+                if !chapters.is_empty() {
+                    self.embed_chapters(&path, &chapters).await?;
+                }
+            }
+            self.record_archive(video_id).await?;
+        }
+
+        let width = format.as_ref().and_then(|format| format.width);
+        let height = format.as_ref().and_then(|format| format.height);
+        let aspect_ratio = match (width, height) {
+            (Some(width), Some(height)) if height > 0 => Some((width as f64) / (height as f64)),
+            _ => None,
+        };
+
+        Ok(DownloadReport {
+            path,
+            format,
+            width,
+            height,
+            aspect_ratio,
+            source_kind,
+            duration: start_time.elapsed(),
+        })
+    }
+}
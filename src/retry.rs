@@ -0,0 +1,89 @@
+use std::{ sync::Arc, time::Duration };
+
+/// How often and how patiently failed operations are retried
+///
+/// The delay doubles after every retry, starting at `base_delay`. Each `RetryPhase` can
+/// override `max_retries`, e.g. to retry cheap player requests aggressively but expensive
+/// downloads sparingly.
+///
+/// # Example
+/// ```
+/// use rustytdown::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_retries: 2,
+///     base_delay: Duration::from_millis(500),
+///     resolve_retries: Some(5),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The number of retries after the first attempt, 0 disables retrying
+    pub max_retries: u32,
+    /// The delay before the first retry
+    pub base_delay: Duration,
+    /// Overrides `max_retries` for player requests that failed in transit
+    pub resolve_retries: Option<u32>,
+    /// Overrides `max_retries` for failed or truncated media downloads
+    pub download_retries: Option<u32>,
+    /// Overrides `max_retries` for conversions FFmpeg rejected as corrupt input
+    pub conversion_retries: Option<u32>,
+}
+
+/// The phase of a download a retry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryPhase {
+    /// The innertube player request resolving the stream URLs
+    Resolve,
+    /// The media download
+    Download,
+    /// The FFmpeg conversion, retried by downloading the source again
+    Conversion,
+}
+
+/// What the `on_retry` callback is told before a retry
+#[derive(Debug, Clone)]
+pub struct RetryInfo {
+    pub phase: RetryPhase,
+    /// The retry about to happen, counting from 1
+    pub attempt: u32,
+    /// The number of retries the phase allows
+    pub max_retries: u32,
+    /// How long the downloader waits before retrying
+    pub delay: Duration,
+    /// The error that triggered the retry
+    pub error: String,
+}
+
+pub(crate) type RetryCallback = Arc<dyn Fn(RetryInfo) + Send + Sync>;
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 1,
+            base_delay: Duration::from_secs(1),
+            resolve_retries: None,
+            download_retries: None,
+            conversion_retries: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the number of retries allowed in a phase
+    pub fn retries_for(&self, phase: RetryPhase) -> u32 {
+        let retries = match phase {
+            RetryPhase::Resolve => self.resolve_retries,
+            RetryPhase::Download => self.download_retries,
+            RetryPhase::Conversion => self.conversion_retries,
+        };
+        retries.unwrap_or(self.max_retries)
+    }
+
+    /// Returns the delay before the given retry, counting from 1
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
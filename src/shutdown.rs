@@ -0,0 +1,99 @@
+use std::{ sync::atomic::{ AtomicBool, AtomicUsize, Ordering }, time::Duration };
+use tokio::sync::Notify;
+
+use crate::{ DownloadControl, Error, Result, YouTubeDownloader };
+
+/// The downloads in flight across the clones of a downloader, and whether it is shutting down
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownState {
+    draining: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+    cancel: DownloadControl,
+}
+
+/// Counts a download as in flight until dropped
+pub(crate) struct InFlight<'a>(&'a ShutdownState);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+impl ShutdownState {
+    /// Registers a download, failing with `Error::ShuttingDown` once `shutdown` was called
+    pub(crate) fn enter(&self) -> Result<InFlight<'_>> {
+        // Counted before checking, so `shutdown` can't miss a download registering concurrently
+        let guard = InFlight(self);
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        match self.draining.load(Ordering::SeqCst) {
+            true => Err(Error::ShuttingDown),
+            false => Ok(guard),
+        }
+    }
+
+    /// Waits until the shutdown timeout ran out and the remaining downloads are cancelled
+    pub(crate) async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+
+    async fn idle(&self) {
+        loop {
+            let idle = self.idle.notified();
+            if self.in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+}
+
+impl YouTubeDownloader {
+    /// Stops accepting downloads and waits for the running ones to finish, up to `timeout`
+    ///
+    /// Meant for a SIGTERM handler: downloads started afterwards fail with
+    /// `Error::ShuttingDown`, running ones keep going until they are written and finalized.
+    /// Transfers still running when `timeout` runs out are cancelled and their partial files
+    /// removed, so no half-written file survives a restart. A batch item whose media is
+    /// already transferring counts as running until its files are moved into place. Applies
+    /// to every clone of the downloader.
+    ///
+    /// Returns true if everything finished in time, false if downloads had to be cancelled.
+    ///
+    /// # Arguments
+    /// * `timeout` - How long running downloads may take to finish
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    /// use std::time::Duration;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// let worker = tokio::spawn({
+    ///     let downloader = downloader.clone();
+    ///     async move { downloader.download_video("dQw4w9WgXcQ").await }
+    /// });
+    ///
+    /// // Once the service is told to stop, e.g. by SIGTERM
+    /// if !downloader.shutdown(Duration::from_secs(30)).await {
+    ///     eprintln!("Cancelled unfinished downloads");
+    /// }
+    /// let _ = worker.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        let state = &self.shutdown_state;
+        state.draining.store(true, Ordering::SeqCst);
+        if tokio::time::timeout(timeout, state.idle()).await.is_ok() {
+            return true;
+        }
+        state.cancel.cancel();
+        state.idle().await;
+        false
+    }
+}
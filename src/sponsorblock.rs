@@ -0,0 +1,206 @@
+use reqwest::StatusCode;
+use serde_json::Value;
+use sha2::{ Digest, Sha256 };
+use std::path::Path;
+use tokio::fs::{ remove_file, rename };
+
+use crate::{ Error, Result, YouTubeDownloader };
+
+/// The SponsorBlock endpoint looking segments up by a prefix of the video ID's SHA-256
+const SKIP_SEGMENTS_URL: &str = "https://sponsor.ajay.app/api/skipSegments";
+
+/// The segment categories requested, as SponsorBlock only returns "sponsor" by default
+const CATEGORIES: &[&str] = &[
+    "sponsor",
+    "selfpromo",
+    "interaction",
+    "intro",
+    "outro",
+    "preview",
+    "music_offtopic",
+    "filler",
+];
+
+/// A time range of a video submitted to SponsorBlock
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// The SponsorBlock category (e.g. "sponsor", "intro", "outro", "selfpromo")
+    pub category: String,
+    /// What players should do with the segment (e.g. "skip", "mute")
+    pub action: String,
+    /// The start of the segment in seconds
+    pub start_seconds: f64,
+    /// The end of the segment in seconds
+    pub end_seconds: f64,
+    /// The SponsorBlock ID of the submission
+    pub uuid: String,
+}
+
+impl YouTubeDownloader {
+    /// Fetches the SponsorBlock segments of a video, ordered by start
+    ///
+    /// Only the first four hex digits of the video ID's SHA-256 are sent, so SponsorBlock
+    /// doesn't learn which video is looked up; the matching entry is picked from the answer.
+    /// Videos without submissions return an empty list.
+    ///
+    /// # Arguments
+    /// * `video_id` - The YouTube video ID (e.g. "dQw4w9WgXcQ")
+    ///
+    /// # Example
+    /// ```
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rustytdown::YouTubeDownloader;
+    ///
+    /// let downloader = YouTubeDownloader::new()?;
+    /// for segment in downloader.get_sponsor_segments("dQw4w9WgXcQ").await? {
+    ///     println!("{} {:.1}s-{:.1}s", segment.category, segment.start_seconds, segment.end_seconds);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sponsor_segments(&self, video_id: &str) -> Result<Vec<Segment>> {
+        let hash: String = Sha256::digest(video_id.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let categories = serde_json::to_string(CATEGORIES).map_err(|e| Error::Api(e.to_string()))?;
+
+        let response = self.client
+            .get(format!("{SKIP_SEGMENTS_URL}/{}", &hash[..4]))
+            .query(&[("categories", categories)])
+            .send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        if !response.status().is_success() {
+            return Err(Error::Api(format!("SponsorBlock request failed with status: {}", response.status())));
+        }
+
+        let videos: Value = response.json().await?;
+        let mut segments: Vec<Segment> = videos
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|video| video["videoID"].as_str() == Some(video_id))
+            .flat_map(|video| video["segments"].as_array().into_iter().flatten())
+            .filter_map(parse_segment)
+            .collect();
+        segments.sort_by(|a, b| a.start_seconds.total_cmp(&b.start_seconds));
+        Ok(segments)
+    }
+
+    /// Removes the skippable segments of `categories` from the media at `path` with FFmpeg
+    ///
+    /// The parts between the segments are joined by the concat demuxer without re-encoding,
+    /// so cuts land on the nearest keyframe.
+    pub(crate) async fn cut_sponsor_segments(
+        &self,
+        video_id: &str,
+        path: &str,
+        categories: &[String]
+    ) -> Result<()> {
+        let segments: Vec<Segment> = self
+            .get_sponsor_segments(video_id).await?
+            .into_iter()
+            .filter(|segment| segment.action == "skip" && categories.contains(&segment.category))
+            .collect();
+        if segments.is_empty() {
+            return Ok(());
+        }
+        // Live streams report no length, their segments are only bounded by the file
+        let length = Some(self.get_info(video_id).await?.length_seconds as f64)
+            .filter(|length| *length > 0.0)
+            .unwrap_or(f64::INFINITY);
+        let removed = merge_ranges(&segments, length);
+        let kept = kept_ranges(&removed, length);
+        if removed.is_empty() {
+            return Ok(());
+        }
+        if kept.is_empty() {
+            return Err(Error::Conversion("Sponsor segments cover the whole video".into()));
+        }
+
+        let path = Path::new(path);
+        let absolute = std::path::absolute(path)?;
+        let list_path = path.with_extension("segments.txt");
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+        let cut_path = path.with_extension(format!("sponsorless.{extension}")).to_string_lossy().into_owned();
+
+        tokio::fs::write(&list_path, concat_list(&absolute, &kept)).await?;
+        let mut args: Vec<String> = ["-y", "-f", "concat", "-safe", "0", "-i"].into_iter().map(String::from).collect();
+        args.push(list_path.to_string_lossy().into_owned());
+        args.extend(["-map", "0", "-c", "copy"].into_iter().map(String::from));
+        args.push(cut_path.clone());
+        let result = Self::run_ffmpeg(args, Some(&cut_path), None).await;
+        remove_file(&list_path).await?;
+        if let Err(e) = result {
+            let _ = remove_file(&cut_path).await;
+            return Err(e);
+        }
+
+        rename(&cut_path, path).await?;
+        Ok(())
+    }
+}
+
+fn parse_segment(segment: &Value) -> Option<Segment> {
+    Some(Segment {
+        category: segment["category"].as_str()?.to_string(),
+        action: segment["actionType"].as_str().unwrap_or("skip").to_string(),
+        start_seconds: segment["segment"][0].as_f64()?,
+        end_seconds: segment["segment"][1].as_f64()?,
+        uuid: segment["UUID"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Merges overlapping and touching segments into sorted ranges clamped to the video length
+fn merge_ranges(segments: &[Segment], length: f64) -> Vec<(f64, f64)> {
+    let mut ranges: Vec<(f64, f64)> = segments
+        .iter()
+        .map(|segment| (segment.start_seconds.max(0.0), segment.end_seconds.min(length)))
+        .filter(|(start, end)| end > start)
+        .collect();
+    ranges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut merged: Vec<(f64, f64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => {
+                last.1 = last.1.max(end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Returns the ranges between the removed ones, `None` as end meaning the end of the file
+///
+/// Gaps under a second are dropped, they are too short to keep after snapping to keyframes.
+fn kept_ranges(removed: &[(f64, f64)], length: f64) -> Vec<(f64, Option<f64>)> {
+    let mut kept = Vec::new();
+    let mut position = 0.0;
+    for &(start, end) in removed {
+        if start - position >= 1.0 {
+            kept.push((position, Some(start)));
+        }
+        position = end;
+    }
+    if length - position >= 1.0 {
+        kept.push((position, None));
+    }
+    kept
+}
+
+/// Builds the concat demuxer script playing the kept ranges of `path` back to back
+fn concat_list(path: &Path, kept: &[(f64, Option<f64>)]) -> String {
+    let file = path.to_string_lossy().replace('\'', "'\\''");
+    let mut list = String::new();
+    for (start, end) in kept {
+        list.push_str(&format!("file '{file}'\ninpoint {start}\n"));
+        if let Some(end) = end {
+            list.push_str(&format!("outpoint {end}\n"));
+        }
+    }
+    list
+}
@@ -0,0 +1,113 @@
+use reqwest::Url;
+use std::{ fmt, str::FromStr };
+
+use crate::{ Error, Result };
+
+/// A validated YouTube video ID, 11 characters out of `A-Z`, `a-z`, `0-9`, `-` and `_`
+///
+/// Methods take IDs as `&str`, which a `VideoId` hands out with `as_str`. Parsing input
+/// into a `VideoId` first catches URLs or typos before any request is made.
+///
+/// # Example
+/// ```
+/// use rustytdown::VideoId;
+///
+/// let id = VideoId::from_url("https://youtu.be/dQw4w9WgXcQ?t=42").unwrap();
+/// assert_eq!(id, VideoId::new("dQw4w9WgXcQ").unwrap());
+/// assert_eq!(VideoId::from_url("www.youtube.com/watch?list=LL&v=dQw4w9WgXcQ").unwrap(), id);
+/// assert!(VideoId::new("https://youtu.be/dQw4w9WgXcQ").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VideoId(String);
+
+impl VideoId {
+    /// Validates a plain video ID (e.g. "dQw4w9WgXcQ")
+    pub fn new(id: &str) -> Result<Self> {
+        let valid = id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        match valid {
+            true => Ok(Self(id.to_string())),
+            false => Err(Error::InvalidVideoId(id.to_string())),
+        }
+    }
+
+    /// Extracts the video ID out of a YouTube URL, accepting a plain ID as well
+    ///
+    /// Understands watch URLs (`youtube.com/watch?v=`, including the mobile and music
+    /// hosts), `youtu.be/` links and `/shorts/`, `/embed/`, `/live/` and `/v/` paths.
+    pub fn from_url(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if let Ok(id) = Self::new(input) {
+            return Ok(id);
+        }
+
+        let invalid = || Error::InvalidVideoId(input.to_string());
+        let with_scheme = match input.contains("://") {
+            true => input.to_string(),
+            false => format!("https://{input}"),
+        };
+        let url = Url::parse(&with_scheme).map_err(|_| invalid())?;
+        let host = url.host_str().ok_or_else(invalid)?.trim_start_matches("www.");
+        let mut segments = url.path_segments().into_iter().flatten().filter(|segment| !segment.is_empty());
+
+        let id = match host {
+            "youtu.be" => segments.next().map(String::from),
+            "youtube.com" | "m.youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+                match segments.next() {
+                    Some("watch") => url.query_pairs().find(|(key, _)| key == "v").map(|(_, id)| id.into_owned()),
+                    Some("shorts" | "embed" | "live" | "v") => segments.next().map(String::from),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        Self::new(&id.ok_or_else(invalid)?).map_err(|_| invalid())
+    }
+
+    /// Returns the ID as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for VideoId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for VideoId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for VideoId {
+    type Err = Error;
+
+    /// Parses a plain ID or a URL, like `VideoId::from_url`
+    fn from_str(input: &str) -> Result<Self> {
+        Self::from_url(input)
+    }
+}
+
+impl TryFrom<&str> for VideoId {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self> {
+        Self::from_url(input)
+    }
+}
+
+impl TryFrom<String> for VideoId {
+    type Error = Error;
+
+    fn try_from(input: String) -> Result<Self> {
+        Self::from_url(&input)
+    }
+}
+
+impl From<VideoId> for String {
+    fn from(id: VideoId) -> Self {
+        id.0
+    }
+}